@@ -0,0 +1,210 @@
+//! Account deserializers. Legacy layouts are recognised by size, the same
+//! way the `migrate_*` instructions do, and returned in the current layout.
+
+use anchor_lang::prelude::*;
+use anchor_lang::{AccountDeserialize, Discriminator};
+use aurum_hybrid_pricing::errors::AurumError;
+use aurum_hybrid_pricing::migration::*;
+use aurum_hybrid_pricing::state::{Config, ReferralAccount, Treasury};
+
+/// Account body after checking the discriminator of `T`
+fn body<T: Discriminator>(data: &[u8]) -> Result<&[u8]> {
+    require!(
+        data.len() >= 8 && data[..8] == T::DISCRIMINATOR,
+        AurumError::InvalidAccountLayout
+    );
+    Ok(&data[8..])
+}
+
+/// Decode any current-layout program account
+pub fn decode<T: AccountDeserialize>(data: &[u8]) -> Result<T> {
+    T::try_deserialize(&mut &data[..])
+}
+
+/// Decode a `Config` account
+pub fn decode_config(data: &[u8]) -> Result<Config> {
+    let body = body::<Config>(data)?;
+    let config = match data.len() {
+        ConfigV0::SPACE => ConfigV0::deserialize(&mut &body[..])
+            .map_err(|_| AurumError::InvalidAccountLayout)?
+            .into(),
+        _ => decode(data)?,
+    };
+    Ok(config)
+}
+
+/// Decode a `Treasury` account
+pub fn decode_treasury(data: &[u8]) -> Result<Treasury> {
+    let body = body::<Treasury>(data)?;
+    let treasury = match data.len() {
+        TreasuryV0::SPACE => TreasuryV0::deserialize(&mut &body[..])
+            .map_err(|_| AurumError::InvalidAccountLayout)?
+            .into(),
+        _ => decode(data)?,
+    };
+    Ok(treasury)
+}
+
+/// Decode a `ReferralAccount`
+pub fn decode_referral(data: &[u8]) -> Result<ReferralAccount> {
+    let body = body::<ReferralAccount>(data)?;
+    let referral = match data.len() {
+        ReferralAccountV0::SPACE => ReferralAccountV0::deserialize(&mut &body[..])
+            .map_err(|_| AurumError::InvalidAccountLayout)?
+            .into(),
+        _ => decode(data)?,
+    };
+    Ok(referral)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aurum_hybrid_pricing::constants::*;
+
+    /// Legacy account bytes: discriminator, the given body, zero padding to `len`
+    fn legacy<T: Discriminator>(body: &[&[u8]], len: usize) -> Vec<u8> {
+        let mut data = T::DISCRIMINATOR.to_vec();
+        for field in body {
+            data.extend_from_slice(field);
+        }
+        data.resize(len, 0);
+        data
+    }
+
+    /// Current account bytes, padded to `len` like an on-chain account
+    fn current<T: AccountSerialize>(value: &T, len: usize) -> Vec<u8> {
+        let mut data = Vec::new();
+        value.try_serialize(&mut data).unwrap();
+        data.resize(len, 0);
+        data
+    }
+
+    fn assert_layout_error<T>(result: Result<T>) {
+        match result.err().expect("expected an error") {
+            Error::AnchorError(err) => {
+                assert_eq!(
+                    err.error_code_number,
+                    u32::from(AurumError::InvalidAccountLayout)
+                )
+            }
+            Error::ProgramError(err) => panic!("unexpected program error {:?}", err),
+        }
+    }
+
+    #[test]
+    fn legacy_config_gets_current_defaults() {
+        let owner = Pubkey::new_unique();
+        let data = legacy::<Config>(
+            &[
+                owner.as_ref(),
+                &1u64.to_le_bytes(),
+                &2u64.to_le_bytes(),
+                &3u64.to_le_bytes(),
+                &4u64.to_le_bytes(),
+                &5u64.to_le_bytes(),
+                &6u64.to_le_bytes(),
+                &[254],
+            ],
+            ConfigV0::SPACE,
+        );
+        let config = decode_config(&data).unwrap();
+
+        assert_eq!(config.version, Config::VERSION);
+        assert_eq!(config.owner, owner);
+        assert_eq!(
+            [config.create_fee, config.lock_fee, config.burn_fee],
+            [1, 2, 3]
+        );
+        assert_eq!(config.referral_discount, 4);
+        assert_eq!(config.referral_rebate, 5);
+        assert_eq!(config.premium_anchor_price, 6);
+        assert_eq!(config.bump, 254);
+        // Same defaults `migrate_config` writes
+        assert_eq!(config.multisend_flat_fee, DEFAULT_MULTISEND_FLAT_FEE);
+        assert_eq!(
+            config.multisend_fee_per_recipient,
+            DEFAULT_MULTISEND_FEE_PER_RECIPIENT
+        );
+        assert!(config.admin_signers.is_empty());
+        assert_eq!(config.credit_discount_bps, 0);
+    }
+
+    #[test]
+    fn current_config_round_trips() {
+        let mut config: Config = ConfigV0 {
+            owner: Pubkey::new_unique(),
+            create_fee: 1,
+            lock_fee: 2,
+            burn_fee: 3,
+            referral_discount: 4,
+            referral_rebate: 5,
+            premium_anchor_price: 6,
+            bump: 254,
+        }
+        .into();
+        config.admin_signers = vec![Pubkey::new_unique(), Pubkey::new_unique()];
+        config.admin_threshold = 2;
+        config.credit_discount_bps = 500;
+
+        let data = current(&config, Config::SPACE);
+        let decoded = decode_config(&data).unwrap();
+        assert_eq!(current(&decoded, Config::SPACE), data);
+    }
+
+    #[test]
+    fn legacy_and_current_treasury() {
+        let data = legacy::<Treasury>(&[&7u64.to_le_bytes(), &[253]], TreasuryV0::SPACE);
+        let treasury = decode_treasury(&data).unwrap();
+        assert_eq!(treasury.version, Treasury::VERSION);
+        assert_eq!(treasury.total_collected, 7);
+        assert_eq!(treasury.total_rebated, 0);
+        assert_eq!(treasury.bump, 253);
+
+        let data = current(&treasury, Treasury::SPACE);
+        assert_eq!(
+            current(&decode_treasury(&data).unwrap(), Treasury::SPACE),
+            data
+        );
+    }
+
+    #[test]
+    fn legacy_and_current_referral() {
+        let referrer = Pubkey::new_unique();
+        let code = "AURUM123";
+        let data = legacy::<ReferralAccount>(
+            &[
+                referrer.as_ref(),
+                &(code.len() as u32).to_le_bytes(),
+                code.as_bytes(),
+                &3u64.to_le_bytes(),
+                &9u64.to_le_bytes(),
+                &252u8.to_le_bytes(),
+            ],
+            ReferralAccountV0::SPACE,
+        );
+        let referral = decode_referral(&data).unwrap();
+        assert_eq!(referral.version, ReferralAccount::VERSION);
+        assert_eq!(referral.referrer, referrer);
+        assert_eq!(referral.code, code);
+        assert_eq!(referral.total_referrals, 3);
+        assert_eq!(referral.rebate_earned, 9);
+        assert_eq!(referral.expires_at, None);
+        assert_eq!(referral.max_uses, None);
+        assert!(referral.is_active);
+
+        let data = current(&referral, ReferralAccount::SPACE);
+        assert_eq!(
+            current(&decode_referral(&data).unwrap(), ReferralAccount::SPACE),
+            data
+        );
+    }
+
+    #[test]
+    fn wrong_discriminator_is_rejected() {
+        let treasury = legacy::<Treasury>(&[&7u64.to_le_bytes(), &[253]], TreasuryV0::SPACE);
+        assert_layout_error(decode_config(&treasury));
+        assert_layout_error(decode_referral(&treasury));
+        assert_layout_error(decode_treasury(&[0; 4]));
+    }
+}
@@ -0,0 +1,192 @@
+//! Sizing for `batch_lock`: how many entries fit in one transaction, and
+//! splitting a longer list into transaction-sized chunks.
+
+use anchor_lang::prelude::*;
+use aurum_hybrid_pricing::constants::MAX_BATCH_LOCK_ENTRIES;
+use aurum_hybrid_pricing::errors::AurumError;
+use aurum_hybrid_pricing::state::BatchLockEntry;
+
+/// Packet size limit for a legacy or v0 transaction
+pub const MAX_TRANSACTION_SIZE: usize = 1232;
+
+/// Limits used to size `batch_lock` transactions
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BatchLimits {
+    /// Serialized transaction size limit
+    pub max_transaction_bytes: usize,
+    /// Bytes used by signatures, header, blockhash, fixed accounts and fixed instruction data
+    pub fixed_overhead_bytes: usize,
+    /// Bytes each lock record adds to the account list (33 without a lookup table, 1 with one)
+    pub per_entry_account_bytes: usize,
+    /// Compute unit limit requested for the transaction
+    pub compute_unit_limit: u32,
+    /// Compute used by fee charging and the escrow transfer
+    pub fixed_compute_units: u32,
+    /// Compute used per entry (PDA derivation, account creation, serialization)
+    pub compute_units_per_entry: u32,
+}
+
+impl Default for BatchLimits {
+    fn default() -> Self {
+        Self {
+            max_transaction_bytes: MAX_TRANSACTION_SIZE,
+            fixed_overhead_bytes: 620,
+            per_entry_account_bytes: 33,
+            compute_unit_limit: 1_400_000,
+            fixed_compute_units: 80_000,
+            compute_units_per_entry: 30_000,
+        }
+    }
+}
+
+impl BatchLimits {
+    /// Largest number of entries that fits in one transaction
+    pub fn max_entries_per_transaction(&self) -> usize {
+        let per_entry_bytes = BatchLockEntry::SERIALIZED_SIZE + self.per_entry_account_bytes;
+        let by_size = self
+            .max_transaction_bytes
+            .saturating_sub(self.fixed_overhead_bytes)
+            / per_entry_bytes.max(1);
+        let by_compute = self
+            .compute_unit_limit
+            .saturating_sub(self.fixed_compute_units)
+            / self.compute_units_per_entry.max(1);
+
+        by_size
+            .min(by_compute as usize)
+            .clamp(1, MAX_BATCH_LOCK_ENTRIES)
+    }
+}
+
+/// One `batch_lock` instruction worth of entries
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BatchLockChunk {
+    /// `base_lock_id` argument for this instruction
+    pub base_lock_id: u64,
+    /// Entries for this instruction; lock ids run from `base_lock_id`
+    pub entries: Vec<BatchLockEntry>,
+}
+
+/// Split `entries` into `batch_lock` chunks that each fit in one transaction.
+/// Lock ids stay contiguous across chunks, starting at `base_lock_id`.
+/// Each chunk is charged `lock_fee` once. Fails if the lock ids would run
+/// past `u64::MAX`.
+pub fn split_batch_lock(
+    base_lock_id: u64,
+    entries: &[BatchLockEntry],
+    limits: &BatchLimits,
+) -> Result<Vec<BatchLockChunk>> {
+    let per_tx = limits.max_entries_per_transaction();
+    entries
+        .chunks(per_tx)
+        .enumerate()
+        .map(|(i, chunk)| {
+            let base_lock_id = base_lock_id
+                .checked_add((i * per_tx) as u64)
+                .ok_or(AurumError::ArithmeticOverflow)?;
+            Ok(BatchLockChunk {
+                base_lock_id,
+                entries: chunk.to_vec(),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entries(count: usize) -> Vec<BatchLockEntry> {
+        (0..count)
+            .map(|i| BatchLockEntry {
+                beneficiary: Pubkey::new_unique(),
+                amount: i as u64 + 1,
+                unlock_time: 1_700_000_000,
+                vesting_end: None,
+            })
+            .collect()
+    }
+
+    /// Limits that fit exactly `per_tx` entries by transaction size
+    fn limits(per_tx: usize) -> BatchLimits {
+        let defaults = BatchLimits::default();
+        BatchLimits {
+            max_transaction_bytes: defaults.fixed_overhead_bytes
+                + per_tx * (BatchLockEntry::SERIALIZED_SIZE + defaults.per_entry_account_bytes),
+            ..defaults
+        }
+    }
+
+    #[test]
+    fn entries_per_transaction_respects_size_compute_and_program_limits() {
+        assert_eq!(limits(4).max_entries_per_transaction(), 4);
+
+        let by_compute = BatchLimits {
+            max_transaction_bytes: usize::MAX,
+            compute_unit_limit: 80_000 + 3 * 30_000,
+            ..BatchLimits::default()
+        };
+        assert_eq!(by_compute.max_entries_per_transaction(), 3);
+
+        let unbounded = BatchLimits {
+            max_transaction_bytes: usize::MAX,
+            compute_unit_limit: u32::MAX,
+            ..BatchLimits::default()
+        };
+        assert_eq!(
+            unbounded.max_entries_per_transaction(),
+            MAX_BATCH_LOCK_ENTRIES
+        );
+
+        // A transaction always carries at least one entry
+        assert_eq!(limits(0).max_entries_per_transaction(), 1);
+    }
+
+    #[test]
+    fn chunks_split_at_the_limit() {
+        for (count, sizes) in [
+            (0, vec![]),
+            (1, vec![1]),
+            (4, vec![4]),
+            (5, vec![4, 1]),
+            (8, vec![4, 4]),
+            (9, vec![4, 4, 1]),
+        ] {
+            let chunks = split_batch_lock(0, &entries(count), &limits(4)).unwrap();
+            let chunk_sizes: Vec<usize> = chunks.iter().map(|chunk| chunk.entries.len()).collect();
+            assert_eq!(chunk_sizes, sizes, "{count} entries");
+        }
+    }
+
+    #[test]
+    fn lock_ids_continue_across_chunks() {
+        let all = entries(10);
+        let chunks = split_batch_lock(100, &all, &limits(4)).unwrap();
+        let bases: Vec<u64> = chunks.iter().map(|chunk| chunk.base_lock_id).collect();
+        assert_eq!(bases, [100, 104, 108]);
+
+        // Entries keep their order, so entry i gets lock id 100 + i
+        let rejoined: Vec<BatchLockEntry> =
+            chunks.into_iter().flat_map(|chunk| chunk.entries).collect();
+        assert_eq!(rejoined, all);
+    }
+
+    #[test]
+    fn lock_id_overflow_is_an_error() {
+        // The last chunk would start past u64::MAX
+        let result = split_batch_lock(u64::MAX - 3, &entries(5), &limits(4));
+        match result.unwrap_err() {
+            Error::AnchorError(err) => {
+                assert_eq!(
+                    err.error_code_number,
+                    u32::from(AurumError::ArithmeticOverflow)
+                )
+            }
+            Error::ProgramError(err) => panic!("unexpected program error {:?}", err),
+        }
+
+        // A single chunk ending at u64::MAX is fine
+        let chunks = split_batch_lock(u64::MAX - 3, &entries(4), &limits(4)).unwrap();
+        assert_eq!(chunks[0].base_lock_id, u64::MAX - 3);
+    }
+}
@@ -0,0 +1,198 @@
+//! Event decoding. `emit!` writes each event as a `Program data:` log line
+//! holding base64 of the event discriminator followed by its Borsh body.
+
+use anchor_lang::prelude::*;
+use anchor_lang::Discriminator;
+use aurum_hybrid_pricing::events::*;
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+
+const PROGRAM_DATA: &str = "Program data: ";
+
+macro_rules! aurum_events {
+    ($($name:ident),* $(,)?) => {
+        /// Every event the program emits
+        pub enum AurumEvent {
+            $($name($name),)*
+        }
+
+        impl AurumEvent {
+            /// Event struct name
+            pub fn name(&self) -> &'static str {
+                match self {
+                    $(Self::$name(_) => stringify!($name),)*
+                }
+            }
+
+            /// Decode discriminator + body; `None` for data that is not an
+            /// AURUM event
+            pub fn decode(data: &[u8]) -> Option<Self> {
+                if data.len() < 8 {
+                    return None;
+                }
+                let (discriminator, mut body) = data.split_at(8);
+                $(
+                    if discriminator == $name::DISCRIMINATOR {
+                        return $name::deserialize(&mut body).ok().map(Self::$name);
+                    }
+                )*
+                None
+            }
+        }
+    };
+}
+
+aurum_events! {
+    FeeCollected,
+    ReferralUsed,
+    PromoCodeUsed,
+    TokenCreated,
+    TokensLocked,
+    BatchLocked,
+    LiquidityLocked,
+    TokensUnlocked,
+    TokenAccountClosed,
+    BurnRecorded,
+    MultisendCompleted,
+    TokensBurned,
+    FeesWithdrawn,
+    FeesDistributed,
+    IntegratorFeePaid,
+    AdminSignersUpdated,
+    ProposalCreated,
+    ProposalApproved,
+    ProposalExecuted,
+    TreasuryPolicyUpdated,
+    DistributorCreated,
+    TokensClaimed,
+    DistributorClawedBack,
+    BurnScheduleCreated,
+    BurnScheduleFunded,
+    ScheduledBurnExecuted,
+    CreditsPurchased,
+    CreditsRefunded,
+    TokensStaked,
+    UnstakeRequested,
+    StakeWithdrawn,
+    StakeDiscountApplied,
+}
+
+/// Events emitted by the program in a transaction's log messages, in order.
+///
+/// Invocations are tracked so `Program data:` lines written by other programs
+/// (including ones this program calls) are skipped.
+pub fn parse_logs<S: AsRef<str>>(logs: &[S]) -> Vec<AurumEvent> {
+    let program_id = crate::ID.to_string();
+    let mut stack: Vec<bool> = Vec::new();
+    let mut events = Vec::new();
+
+    for line in logs {
+        let line = line.as_ref();
+        if let Some(data) = line.strip_prefix(PROGRAM_DATA) {
+            if stack.last() == Some(&true) {
+                events.extend(
+                    STANDARD
+                        .decode(data)
+                        .ok()
+                        .and_then(|bytes| AurumEvent::decode(&bytes)),
+                );
+            }
+        } else if let Some(rest) = line.strip_prefix("Program ") {
+            let mut words = rest.split(' ');
+            let program = words.next().unwrap_or_default();
+            match words.next() {
+                Some("invoke") => stack.push(program == program_id),
+                Some("success") | Some("failed:") => {
+                    stack.pop();
+                }
+                _ => {}
+            }
+        }
+    }
+    events
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::Event;
+    use aurum_hybrid_pricing::state::OperationKind;
+
+    const TOKEN_PROGRAM: &str = "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA";
+
+    fn fee_collected(amount: u64) -> FeeCollected {
+        FeeCollected {
+            payer: Pubkey::new_unique(),
+            amount,
+            fee_type: OperationKind::Burn,
+            exemption_discount: None,
+            paid_with_credit: false,
+            timestamp: 1_700_000_000,
+        }
+    }
+
+    fn data_line(event: &impl Event) -> String {
+        format!("{PROGRAM_DATA}{}", STANDARD.encode(event.data()))
+    }
+
+    fn amounts(events: &[AurumEvent]) -> Vec<u64> {
+        events
+            .iter()
+            .map(|event| match event {
+                AurumEvent::FeeCollected(e) => e.amount,
+                other => panic!("unexpected event {}", other.name()),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn events_around_a_nested_call_are_kept() {
+        let aurum = crate::ID.to_string();
+        let logs = vec![
+            format!("Program {aurum} invoke [1]"),
+            "Program log: Instruction: BurnTokens".to_string(),
+            data_line(&fee_collected(1)),
+            format!("Program {TOKEN_PROGRAM} invoke [2]"),
+            "Program log: Instruction: Burn".to_string(),
+            // Looks like an AURUM event, but the token program wrote it
+            data_line(&fee_collected(2)),
+            format!("Program {TOKEN_PROGRAM} consumed 4000 of 190000 compute units"),
+            format!("Program {TOKEN_PROGRAM} success"),
+            data_line(&fee_collected(3)),
+            format!("Program {aurum} consumed 20000 of 200000 compute units"),
+            format!("Program {aurum} success"),
+        ];
+        assert_eq!(amounts(&parse_logs(&logs)), [1, 3]);
+    }
+
+    #[test]
+    fn other_programs_data_is_skipped() {
+        let other = Pubkey::new_unique().to_string();
+        let logs = vec![
+            format!("Program {other} invoke [1]"),
+            data_line(&fee_collected(1)),
+            format!("Program {other} success"),
+            // Outside any invocation
+            data_line(&fee_collected(2)),
+        ];
+        assert!(parse_logs(&logs).is_empty());
+    }
+
+    #[test]
+    fn failed_invocations_are_popped() {
+        let aurum = crate::ID.to_string();
+        let other = Pubkey::new_unique().to_string();
+        let logs = vec![
+            format!("Program {aurum} invoke [1]"),
+            format!("Program {other} invoke [2]"),
+            data_line(&fee_collected(1)),
+            format!("Program {other} failed: custom program error: 0x1"),
+            data_line(&fee_collected(2)),
+            "Program data: not base64!".to_string(),
+            format!("{PROGRAM_DATA}{}", STANDARD.encode([0u8; 4])),
+            format!("Program {aurum} failed: custom program error: 0x1"),
+            data_line(&fee_collected(3)),
+        ];
+        assert_eq!(amounts(&parse_logs(&logs)), [2]);
+    }
+}
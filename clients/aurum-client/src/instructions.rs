@@ -0,0 +1,1305 @@
+//! Instruction builders. Accounts are laid out through the program's generated
+//! `accounts::*` structs, so the order always matches the on-chain context;
+//! unused optional accounts are passed as the program id, as Anchor expects.
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::solana_program::{system_program, sysvar};
+use anchor_lang::InstructionData;
+use anchor_spl::associated_token::{self, get_associated_token_address};
+use anchor_spl::token;
+use aurum_hybrid_pricing::state::{AdminAction, BatchLockEntry, FeeRecipient, OperationKind, PricingUpdate, StakeTier};
+use aurum_hybrid_pricing::{accounts, instruction, ID};
+
+use crate::pda;
+
+/// Optional discounts and integrator routing for a paid instruction
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct FeeOptions {
+    /// Referrer wallet and the referral code to apply
+    pub referral: Option<(Pubkey, String)>,
+    /// Admin promo code to apply
+    pub promo_code: Option<String>,
+    /// Integrator authority and its payout wallet
+    pub integrator: Option<(Pubkey, Pubkey)>,
+    /// Wallet holding a fee exemption; must be the payer
+    pub fee_exemption: Option<Pubkey>,
+    /// Owner and operation of prepaid credits to spend instead of lamports;
+    /// the owner must be the payer
+    pub credits: Option<(Pubkey, OperationKind)>,
+    /// Staker whose tier discount applies; must be the payer
+    pub stake: Option<Pubkey>,
+}
+
+impl FeeOptions {
+    fn referral_code(&self) -> Option<String> {
+        self.referral.as_ref().map(|(_, code)| code.clone())
+    }
+
+    /// Optional fee accounts shared by every paid instruction
+    fn keys(&self) -> accounts::OptionalFeeAccounts {
+        accounts::OptionalFeeAccounts {
+            referral_account: self.referral.as_ref().map(|(referrer, _)| pda::referral(referrer).0),
+            referrer: self.referral.as_ref().map(|(referrer, _)| *referrer),
+            promo_code: self.promo_code.as_deref().map(|code| pda::promo_code(code).0),
+            integrator: self.integrator.map(|(authority, _)| pda::integrator(&authority).0),
+            integrator_payout: self.integrator.map(|(_, payout)| payout),
+            fee_exemption: self.fee_exemption.map(|wallet| pda::fee_exemption(&wallet).0),
+            credit_account: self.credits.map(|(owner, operation)| pda::credit_account(&owner, operation).0),
+            stake_account: self.stake.map(|owner| pda::stake_account(&owner).0),
+        }
+    }
+}
+
+fn build(
+    accounts: impl ToAccountMetas,
+    data: impl InstructionData,
+    remaining: impl IntoIterator<Item = AccountMeta>,
+) -> Instruction {
+    let mut metas = accounts.to_account_metas(None);
+    metas.extend(remaining);
+    Instruction {
+        program_id: ID,
+        accounts: metas,
+        data: data.data(),
+    }
+}
+
+/// Additional admin multisig signers, passed as remaining accounts
+fn co_signer_metas(co_signers: &[Pubkey]) -> Vec<AccountMeta> {
+    co_signers
+        .iter()
+        .map(|signer| AccountMeta::new_readonly(*signer, true))
+        .collect()
+}
+
+pub fn initialize_config(owner: &Pubkey) -> Instruction {
+    build(
+        accounts::InitializeConfig {
+            config: pda::config().0,
+            treasury: pda::treasury().0,
+            policy: pda::treasury_policy().0,
+            owner: *owner,
+            system_program: system_program::ID,
+        },
+        instruction::InitializeConfig {},
+        [],
+    )
+}
+
+pub fn update_pricing(owner: &Pubkey, update: &PricingUpdate, co_signers: &[Pubkey]) -> Instruction {
+    build(
+        accounts::UpdatePricing {
+            config: pda::config().0,
+            owner: *owner,
+        },
+        instruction::UpdatePricing {
+            create_fee: update.create_fee,
+            lock_fee: update.lock_fee,
+            burn_fee: update.burn_fee,
+            referral_discount: update.referral_discount,
+            referral_rebate: update.referral_rebate,
+            premium_anchor_price: update.premium_anchor_price,
+        },
+        co_signer_metas(co_signers),
+    )
+}
+
+pub fn update_multisend_pricing(
+    owner: &Pubkey,
+    flat_fee: Option<u64>,
+    fee_per_recipient: Option<u64>,
+    co_signers: &[Pubkey],
+) -> Instruction {
+    build(
+        accounts::UpdatePricing {
+            config: pda::config().0,
+            owner: *owner,
+        },
+        instruction::UpdateMultisendPricing {
+            flat_fee,
+            fee_per_recipient,
+        },
+        co_signer_metas(co_signers),
+    )
+}
+
+pub fn update_credit_pricing(
+    owner: &Pubkey,
+    discount_bps: Option<u16>,
+    refund_fee_bps: Option<u16>,
+    co_signers: &[Pubkey],
+) -> Instruction {
+    build(
+        accounts::UpdatePricing {
+            config: pda::config().0,
+            owner: *owner,
+        },
+        instruction::UpdateCreditPricing {
+            discount_bps,
+            refund_fee_bps,
+        },
+        co_signer_metas(co_signers),
+    )
+}
+
+pub fn set_stake_tiers(owner: &Pubkey, tiers: Vec<StakeTier>, co_signers: &[Pubkey]) -> Instruction {
+    build(
+        accounts::UpdatePricing {
+            config: pda::config().0,
+            owner: *owner,
+        },
+        instruction::SetStakeTiers { tiers },
+        co_signer_metas(co_signers),
+    )
+}
+
+/// Read-only fee quote; simulate it and decode the return data with
+/// `quote::decode_quote`. `fees.integrator`'s payout wallet is not needed.
+pub fn quote_fee(payer: &Pubkey, operation: OperationKind, recipients: u64, fees: &FeeOptions) -> Instruction {
+    let keys = fees.keys();
+    build(
+        accounts::QuoteFee {
+            config: pda::config().0,
+            payer: *payer,
+            referral_account: keys.referral_account,
+            promo_code: keys.promo_code,
+            integrator: keys.integrator,
+            fee_exemption: keys.fee_exemption,
+            stake_account: keys.stake_account,
+            credit_account: keys.credit_account,
+        },
+        instruction::QuoteFee {
+            operation,
+            recipients,
+            referral_code: fees.referral_code(),
+        },
+        [],
+    )
+}
+
+pub fn set_admin_signers(
+    owner: &Pubkey,
+    signers: Vec<Pubkey>,
+    threshold: u8,
+    co_signers: &[Pubkey],
+) -> Instruction {
+    build(
+        accounts::SetAdminSigners {
+            config: pda::config().0,
+            owner: *owner,
+        },
+        instruction::SetAdminSigners { signers, threshold },
+        co_signer_metas(co_signers),
+    )
+}
+
+/// `proposal_id` must be the current `Config.proposal_count`
+pub fn create_proposal(proposer: &Pubkey, proposal_id: u64, action: AdminAction) -> Instruction {
+    build(
+        accounts::CreateProposal {
+            config: pda::config().0,
+            proposal: pda::proposal(proposal_id).0,
+            proposer: *proposer,
+            system_program: system_program::ID,
+        },
+        instruction::CreateProposal { action },
+        [],
+    )
+}
+
+pub fn approve_proposal(approver: &Pubkey, proposal_id: u64) -> Instruction {
+    build(
+        accounts::ApproveProposal {
+            config: pda::config().0,
+            proposal: pda::proposal(proposal_id).0,
+            approver: *approver,
+        },
+        instruction::ApproveProposal {},
+        [],
+    )
+}
+
+/// `withdraw_destination` is required for `WithdrawFees` proposals
+pub fn execute_proposal(
+    executor: &Pubkey,
+    proposal_id: u64,
+    withdraw_destination: Option<Pubkey>,
+) -> Instruction {
+    build(
+        accounts::ExecuteProposal {
+            config: pda::config().0,
+            proposal: pda::proposal(proposal_id).0,
+            treasury: pda::treasury().0,
+            policy: withdraw_destination.map(|_| pda::treasury_policy().0),
+            destination: withdraw_destination,
+            executor: *executor,
+        },
+        instruction::ExecuteProposal {},
+        [],
+    )
+}
+
+pub fn cancel_proposal(proposer: &Pubkey, proposal_id: u64) -> Instruction {
+    build(
+        accounts::CancelProposal {
+            proposal: pda::proposal(proposal_id).0,
+            proposer: *proposer,
+        },
+        instruction::CancelProposal {},
+        [],
+    )
+}
+
+pub fn create_referral(user: &Pubkey) -> Instruction {
+    build(
+        accounts::CreateReferral {
+            referral_account: pda::referral(user).0,
+            user: *user,
+            system_program: system_program::ID,
+        },
+        instruction::CreateReferral {},
+        [],
+    )
+}
+
+/// `None` leaves a field unchanged; the clear flags remove it
+pub fn update_referral(
+    referrer: &Pubkey,
+    expires_at: Option<i64>,
+    max_uses: Option<u64>,
+    clear_expiry: bool,
+    clear_max_uses: bool,
+) -> Instruction {
+    build(
+        accounts::UpdateReferral {
+            referral_account: pda::referral(referrer).0,
+            referrer: *referrer,
+        },
+        instruction::UpdateReferral {
+            expires_at,
+            max_uses,
+            clear_expiry,
+            clear_max_uses,
+        },
+        [],
+    )
+}
+
+/// `authority` is the referrer or an admin; `co_signers` are only needed for
+/// an admin multisig
+pub fn deactivate_referral(authority: &Pubkey, referrer: &Pubkey, co_signers: &[Pubkey]) -> Instruction {
+    build(
+        accounts::DeactivateReferral {
+            referral_account: pda::referral(referrer).0,
+            config: pda::config().0,
+            authority: *authority,
+        },
+        instruction::DeactivateReferral {},
+        co_signer_metas(co_signers),
+    )
+}
+
+pub fn close_referral(referrer: &Pubkey) -> Instruction {
+    build(
+        accounts::CloseReferral {
+            referral_account: pda::referral(referrer).0,
+            referrer: *referrer,
+        },
+        instruction::CloseReferral {},
+        [],
+    )
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn create_promo_code(
+    owner: &Pubkey,
+    code: String,
+    discount_bps: u16,
+    operations: u8,
+    allow_referral: bool,
+    expires_at: Option<i64>,
+    max_uses: Option<u64>,
+    co_signers: &[Pubkey],
+) -> Instruction {
+    build(
+        accounts::CreatePromoCode {
+            promo_code: pda::promo_code(&code).0,
+            config: pda::config().0,
+            owner: *owner,
+            system_program: system_program::ID,
+        },
+        instruction::CreatePromoCode {
+            code,
+            discount_bps,
+            operations,
+            allow_referral,
+            expires_at,
+            max_uses,
+        },
+        co_signer_metas(co_signers),
+    )
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn update_promo_code(
+    owner: &Pubkey,
+    code: &str,
+    discount_bps: Option<u16>,
+    operations: Option<u8>,
+    allow_referral: Option<bool>,
+    expires_at: Option<i64>,
+    max_uses: Option<u64>,
+    is_active: Option<bool>,
+    clear_expiry: bool,
+    clear_max_uses: bool,
+    co_signers: &[Pubkey],
+) -> Instruction {
+    build(
+        accounts::UpdatePromoCode {
+            promo_code: pda::promo_code(code).0,
+            config: pda::config().0,
+            owner: *owner,
+        },
+        instruction::UpdatePromoCode {
+            discount_bps,
+            operations,
+            allow_referral,
+            expires_at,
+            max_uses,
+            is_active,
+            clear_expiry,
+            clear_max_uses,
+        },
+        co_signer_metas(co_signers),
+    )
+}
+
+pub fn close_promo_code(owner: &Pubkey, code: &str, co_signers: &[Pubkey]) -> Instruction {
+    build(
+        accounts::ClosePromoCode {
+            promo_code: pda::promo_code(code).0,
+            config: pda::config().0,
+            owner: *owner,
+        },
+        instruction::ClosePromoCode {},
+        co_signer_metas(co_signers),
+    )
+}
+
+pub fn create_fee_exemption(
+    owner: &Pubkey,
+    wallet: &Pubkey,
+    discount_bps: u16,
+    expires_at: Option<i64>,
+    co_signers: &[Pubkey],
+) -> Instruction {
+    build(
+        accounts::CreateFeeExemption {
+            fee_exemption: pda::fee_exemption(wallet).0,
+            config: pda::config().0,
+            owner: *owner,
+            system_program: system_program::ID,
+        },
+        instruction::CreateFeeExemption {
+            wallet: *wallet,
+            discount_bps,
+            expires_at,
+        },
+        co_signer_metas(co_signers),
+    )
+}
+
+pub fn update_fee_exemption(
+    owner: &Pubkey,
+    wallet: &Pubkey,
+    discount_bps: Option<u16>,
+    expires_at: Option<i64>,
+    clear_expiry: bool,
+    co_signers: &[Pubkey],
+) -> Instruction {
+    build(
+        accounts::UpdateFeeExemption {
+            fee_exemption: pda::fee_exemption(wallet).0,
+            config: pda::config().0,
+            owner: *owner,
+        },
+        instruction::UpdateFeeExemption {
+            discount_bps,
+            expires_at,
+            clear_expiry,
+        },
+        co_signer_metas(co_signers),
+    )
+}
+
+pub fn close_fee_exemption(owner: &Pubkey, wallet: &Pubkey, co_signers: &[Pubkey]) -> Instruction {
+    build(
+        accounts::CloseFeeExemption {
+            fee_exemption: pda::fee_exemption(wallet).0,
+            config: pda::config().0,
+            owner: *owner,
+        },
+        instruction::CloseFeeExemption {},
+        co_signer_metas(co_signers),
+    )
+}
+
+pub fn buy_credits(owner: &Pubkey, operation: OperationKind, count: u64) -> Instruction {
+    build(
+        accounts::BuyCredits {
+            credit_account: pda::credit_account(owner, operation).0,
+            config: pda::config().0,
+            owner: *owner,
+            system_program: system_program::ID,
+        },
+        instruction::BuyCredits { operation, count },
+        [],
+    )
+}
+
+pub fn refund_credits(owner: &Pubkey, operation: OperationKind) -> Instruction {
+    build(
+        accounts::RefundCredits {
+            credit_account: pda::credit_account(owner, operation).0,
+            config: pda::config().0,
+            treasury: pda::treasury().0,
+            owner: *owner,
+        },
+        instruction::RefundCredits {},
+        [],
+    )
+}
+
+pub fn initialize_staking(
+    owner: &Pubkey,
+    mint: &Pubkey,
+    cooldown: i64,
+    co_signers: &[Pubkey],
+) -> Instruction {
+    build(
+        accounts::InitializeStaking {
+            staking_pool: pda::staking_pool().0,
+            mint: *mint,
+            vault: pda::staking_vault(mint),
+            config: pda::config().0,
+            owner: *owner,
+            token_program: token::ID,
+            associated_token_program: associated_token::ID,
+            system_program: system_program::ID,
+        },
+        instruction::InitializeStaking { cooldown },
+        co_signer_metas(co_signers),
+    )
+}
+
+/// `mint` is the staking pool's mint; tokens come from the owner's ATA
+pub fn stake(owner: &Pubkey, mint: &Pubkey, amount: u64) -> Instruction {
+    build(
+        accounts::Stake {
+            staking_pool: pda::staking_pool().0,
+            vault: pda::staking_vault(mint),
+            stake_account: pda::stake_account(owner).0,
+            owner_token_account: get_associated_token_address(owner, mint),
+            owner: *owner,
+            token_program: token::ID,
+            system_program: system_program::ID,
+        },
+        instruction::Stake { amount },
+        [],
+    )
+}
+
+pub fn unstake(owner: &Pubkey, amount: u64) -> Instruction {
+    build(
+        accounts::Unstake {
+            staking_pool: pda::staking_pool().0,
+            stake_account: pda::stake_account(owner).0,
+            owner: *owner,
+        },
+        instruction::Unstake { amount },
+        [],
+    )
+}
+
+/// Withdraw to the owner's ATA for `mint`, which must exist
+pub fn withdraw_stake(owner: &Pubkey, mint: &Pubkey) -> Instruction {
+    build(
+        accounts::WithdrawStake {
+            staking_pool: pda::staking_pool().0,
+            vault: pda::staking_vault(mint),
+            stake_account: pda::stake_account(owner).0,
+            owner_token_account: get_associated_token_address(owner, mint),
+            owner: *owner,
+            token_program: token::ID,
+        },
+        instruction::WithdrawStake {},
+        [],
+    )
+}
+
+/// `mint` is a fresh keypair that must also sign
+pub fn create_token(payer: &Pubkey, mint: &Pubkey, decimals: u8, fees: &FeeOptions) -> Instruction {
+    build(
+        accounts::CreateToken {
+            mint: *mint,
+            config: pda::config().0,
+            treasury: pda::treasury().0,
+            fee_accounts: fees.keys(),
+            payer: *payer,
+            token_program: token::ID,
+            system_program: system_program::ID,
+            rent: sysvar::rent::ID,
+        },
+        instruction::CreateToken {
+            decimals,
+            referral_code: fees.referral_code(),
+        },
+        [],
+    )
+}
+
+pub fn lock_tokens(
+    locker: &Pubkey,
+    mint: &Pubkey,
+    amount: u64,
+    unlock_time: i64,
+    fees: &FeeOptions,
+) -> Instruction {
+    build(
+        accounts::LockTokens {
+            mint: *mint,
+            user_token_account: get_associated_token_address(locker, mint),
+            escrow_token_account: pda::escrow(mint),
+            config: pda::config().0,
+            treasury: pda::treasury().0,
+            fee_accounts: fees.keys(),
+            locker: *locker,
+            token_program: token::ID,
+            associated_token_program: associated_token::ID,
+            system_program: system_program::ID,
+        },
+        instruction::LockTokens {
+            amount,
+            unlock_time,
+            referral_code: fees.referral_code(),
+        },
+        [],
+    )
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn lock_lp_tokens(
+    locker: &Pubkey,
+    lp_mint: &Pubkey,
+    pool: &Pubkey,
+    lock_id: u64,
+    amount: u64,
+    unlock_time: i64,
+    fees: &FeeOptions,
+) -> Instruction {
+    build(
+        accounts::LockLpTokens {
+            mint: *lp_mint,
+            pool: *pool,
+            user_token_account: get_associated_token_address(locker, lp_mint),
+            escrow_token_account: pda::escrow(lp_mint),
+            lock_record: pda::lock_record(lp_mint, locker, lock_id).0,
+            config: pda::config().0,
+            treasury: pda::treasury().0,
+            fee_accounts: fees.keys(),
+            locker: *locker,
+            token_program: token::ID,
+            associated_token_program: associated_token::ID,
+            system_program: system_program::ID,
+        },
+        instruction::LockLpTokens {
+            lock_id,
+            amount,
+            unlock_time,
+            referral_code: fees.referral_code(),
+        },
+        [],
+    )
+}
+
+/// Lock record PDAs for ids `base_lock_id..` are appended as remaining accounts
+pub fn batch_lock(
+    locker: &Pubkey,
+    mint: &Pubkey,
+    base_lock_id: u64,
+    entries: Vec<BatchLockEntry>,
+    fees: &FeeOptions,
+) -> Instruction {
+    let records: Vec<AccountMeta> = (0..entries.len() as u64)
+        .map(|i| AccountMeta::new(pda::lock_record(mint, locker, base_lock_id + i).0, false))
+        .collect();
+    build(
+        accounts::BatchLock {
+            mint: *mint,
+            user_token_account: get_associated_token_address(locker, mint),
+            escrow_token_account: pda::escrow(mint),
+            config: pda::config().0,
+            treasury: pda::treasury().0,
+            fee_accounts: fees.keys(),
+            locker: *locker,
+            token_program: token::ID,
+            associated_token_program: associated_token::ID,
+            system_program: system_program::ID,
+        },
+        instruction::BatchLock {
+            base_lock_id,
+            entries,
+            referral_code: fees.referral_code(),
+        },
+        records,
+    )
+}
+
+pub fn unlock_tokens(beneficiary: &Pubkey, locker: &Pubkey, mint: &Pubkey, lock_id: u64) -> Instruction {
+    build(
+        accounts::UnlockTokens {
+            mint: *mint,
+            lock_record: pda::lock_record(mint, locker, lock_id).0,
+            escrow_token_account: pda::escrow(mint),
+            beneficiary_token_account: get_associated_token_address(beneficiary, mint),
+            treasury: pda::treasury().0,
+            locker: *locker,
+            beneficiary: *beneficiary,
+            token_program: token::ID,
+            associated_token_program: associated_token::ID,
+            system_program: system_program::ID,
+        },
+        instruction::UnlockTokens {},
+        [],
+    )
+}
+
+/// `recipients` are `(wallet, amount)` pairs; their ATAs are derived
+pub fn multisend(
+    sender: &Pubkey,
+    mint: &Pubkey,
+    recipients: &[(Pubkey, u64)],
+    create_missing_atas: bool,
+    fees: &FeeOptions,
+) -> Instruction {
+    let remaining = recipients.iter().flat_map(|(wallet, _)| {
+        [
+            AccountMeta::new_readonly(*wallet, false),
+            AccountMeta::new(get_associated_token_address(wallet, mint), false),
+        ]
+    });
+    build(
+        accounts::Multisend {
+            mint: *mint,
+            sender_token_account: get_associated_token_address(sender, mint),
+            config: pda::config().0,
+            treasury: pda::treasury().0,
+            fee_accounts: fees.keys(),
+            sender: *sender,
+            token_program: token::ID,
+            associated_token_program: associated_token::ID,
+            system_program: system_program::ID,
+        },
+        instruction::Multisend {
+            amounts: recipients.iter().map(|(_, amount)| *amount).collect(),
+            create_missing_atas,
+            referral_code: fees.referral_code(),
+        },
+        remaining.collect::<Vec<_>>(),
+    )
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn create_distributor(
+    authority: &Pubkey,
+    mint: &Pubkey,
+    distributor_id: u64,
+    merkle_root: [u8; 32],
+    total_amount: u64,
+    max_num_nodes: u64,
+    clawback_time: i64,
+    fees: &FeeOptions,
+) -> Instruction {
+    let distributor = pda::distributor(authority, distributor_id).0;
+    build(
+        accounts::CreateDistributor {
+            mint: *mint,
+            distributor,
+            vault: get_associated_token_address(&distributor, mint),
+            authority_token_account: get_associated_token_address(authority, mint),
+            config: pda::config().0,
+            treasury: pda::treasury().0,
+            fee_accounts: fees.keys(),
+            authority: *authority,
+            token_program: token::ID,
+            associated_token_program: associated_token::ID,
+            system_program: system_program::ID,
+        },
+        instruction::CreateDistributor {
+            distributor_id,
+            merkle_root,
+            total_amount,
+            max_num_nodes,
+            clawback_time,
+            referral_code: fees.referral_code(),
+        },
+        [],
+    )
+}
+
+pub fn claim(
+    claimant: &Pubkey,
+    distributor: &Pubkey,
+    mint: &Pubkey,
+    index: u64,
+    amount: u64,
+    proof: Vec<[u8; 32]>,
+) -> Instruction {
+    build(
+        accounts::Claim {
+            mint: *mint,
+            distributor: *distributor,
+            vault: get_associated_token_address(distributor, mint),
+            claim_status: pda::claim_status(distributor, index).0,
+            claimant_token_account: get_associated_token_address(claimant, mint),
+            claimant: *claimant,
+            token_program: token::ID,
+            associated_token_program: associated_token::ID,
+            system_program: system_program::ID,
+        },
+        instruction::Claim { index, amount, proof },
+        [],
+    )
+}
+
+pub fn clawback(authority: &Pubkey, mint: &Pubkey, distributor_id: u64) -> Instruction {
+    let distributor = pda::distributor(authority, distributor_id).0;
+    build(
+        accounts::Clawback {
+            mint: *mint,
+            distributor,
+            vault: get_associated_token_address(&distributor, mint),
+            authority_token_account: get_associated_token_address(authority, mint),
+            authority: *authority,
+            token_program: token::ID,
+            associated_token_program: associated_token::ID,
+            system_program: system_program::ID,
+        },
+        instruction::Clawback {},
+        [],
+    )
+}
+
+/// `receipt_sequence` is the mint's current `BurnStats.burn_count` (0 before
+/// the first burn); pass it to issue a `BurnReceipt`
+pub fn burn_tokens(
+    burner: &Pubkey,
+    mint: &Pubkey,
+    amount: u64,
+    receipt_sequence: Option<u64>,
+    fees: &FeeOptions,
+) -> Instruction {
+    build(
+        accounts::BurnTokens {
+            mint: *mint,
+            user_token_account: get_associated_token_address(burner, mint),
+            burn_stats: pda::burn_stats(mint).0,
+            burn_receipt: receipt_sequence.map(|sequence| pda::burn_receipt(mint, sequence).0),
+            config: pda::config().0,
+            treasury: pda::treasury().0,
+            fee_accounts: fees.keys(),
+            burner: *burner,
+            token_program: token::ID,
+            associated_token_program: associated_token::ID,
+            system_program: system_program::ID,
+        },
+        instruction::BurnTokens {
+            amount,
+            referral_code: fees.referral_code(),
+        },
+        [],
+    )
+}
+
+pub fn burn_all_and_close(burner: &Pubkey, mint: &Pubkey, fees: &FeeOptions) -> Instruction {
+    build(
+        accounts::BurnAllAndClose {
+            mint: *mint,
+            user_token_account: get_associated_token_address(burner, mint),
+            burn_stats: pda::burn_stats(mint).0,
+            config: pda::config().0,
+            treasury: pda::treasury().0,
+            fee_accounts: fees.keys(),
+            burner: *burner,
+            token_program: token::ID,
+            associated_token_program: associated_token::ID,
+            system_program: system_program::ID,
+        },
+        instruction::BurnAllAndClose {
+            referral_code: fees.referral_code(),
+        },
+        [],
+    )
+}
+
+/// `token_accounts` are `(token account, mint)` pairs owned by `owner`, each
+/// holding at most `MAX_DUST_AMOUNT`
+pub fn close_dust_accounts(owner: &Pubkey, token_accounts: &[(Pubkey, Pubkey)]) -> Instruction {
+    let remaining = token_accounts.iter().flat_map(|(account, mint)| {
+        [AccountMeta::new(*account, false), AccountMeta::new(*mint, false)]
+    });
+    build(
+        accounts::CloseDustAccounts {
+            owner: *owner,
+            token_program: token::ID,
+        },
+        instruction::CloseDustAccounts {},
+        remaining.collect::<Vec<_>>(),
+    )
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn create_burn_schedule(
+    authority: &Pubkey,
+    mint: &Pubkey,
+    schedule_id: u64,
+    amount_per_interval: u64,
+    interval: i64,
+    start_time: i64,
+    bounty_per_crank: u64,
+    fees: &FeeOptions,
+) -> Instruction {
+    let schedule = pda::burn_schedule(mint, authority, schedule_id).0;
+    build(
+        accounts::CreateBurnSchedule {
+            mint: *mint,
+            schedule,
+            vault: get_associated_token_address(&schedule, mint),
+            config: pda::config().0,
+            treasury: pda::treasury().0,
+            fee_accounts: fees.keys(),
+            authority: *authority,
+            token_program: token::ID,
+            associated_token_program: associated_token::ID,
+            system_program: system_program::ID,
+        },
+        instruction::CreateBurnSchedule {
+            schedule_id,
+            amount_per_interval,
+            interval,
+            start_time,
+            bounty_per_crank,
+            referral_code: fees.referral_code(),
+        },
+        [],
+    )
+}
+
+pub fn fund_burn_schedule(
+    funder: &Pubkey,
+    schedule: &Pubkey,
+    mint: &Pubkey,
+    token_amount: u64,
+    bounty_lamports: u64,
+) -> Instruction {
+    build(
+        accounts::FundBurnSchedule {
+            mint: *mint,
+            schedule: *schedule,
+            vault: get_associated_token_address(schedule, mint),
+            funder_token_account: get_associated_token_address(funder, mint),
+            funder: *funder,
+            token_program: token::ID,
+            system_program: system_program::ID,
+        },
+        instruction::FundBurnSchedule {
+            token_amount,
+            bounty_lamports,
+        },
+        [],
+    )
+}
+
+pub fn crank_burn(cranker: &Pubkey, schedule: &Pubkey, mint: &Pubkey) -> Instruction {
+    build(
+        accounts::CrankBurn {
+            mint: *mint,
+            schedule: *schedule,
+            vault: get_associated_token_address(schedule, mint),
+            burn_stats: pda::burn_stats(mint).0,
+            cranker: *cranker,
+            token_program: token::ID,
+            system_program: system_program::ID,
+        },
+        instruction::CrankBurn {},
+        [],
+    )
+}
+
+/// Pays `owner` when `destination` is `None`
+pub fn withdraw_fees(
+    owner: &Pubkey,
+    amount: u64,
+    destination: Option<Pubkey>,
+    co_signers: &[Pubkey],
+) -> Instruction {
+    build(
+        accounts::WithdrawFees {
+            config: pda::config().0,
+            treasury: pda::treasury().0,
+            policy: pda::treasury_policy().0,
+            destination,
+            owner: *owner,
+            system_program: system_program::ID,
+        },
+        instruction::WithdrawFees { amount },
+        co_signer_metas(co_signers),
+    )
+}
+
+pub fn set_treasury_policy(
+    owner: &Pubkey,
+    allowed_destinations: Vec<Pubkey>,
+    epoch_cap: u64,
+    cooldown_seconds: i64,
+    co_signers: &[Pubkey],
+) -> Instruction {
+    build(
+        accounts::SetTreasuryPolicy {
+            config: pda::config().0,
+            policy: pda::treasury_policy().0,
+            owner: *owner,
+            system_program: system_program::ID,
+        },
+        instruction::SetTreasuryPolicy {
+            allowed_destinations,
+            epoch_cap,
+            cooldown_seconds,
+        },
+        co_signer_metas(co_signers),
+    )
+}
+
+pub fn add_withdrawal_destination(owner: &Pubkey, destination: Pubkey, co_signers: &[Pubkey]) -> Instruction {
+    build(
+        accounts::UpdateTreasuryPolicy {
+            config: pda::config().0,
+            policy: pda::treasury_policy().0,
+            owner: *owner,
+        },
+        instruction::AddWithdrawalDestination { destination },
+        co_signer_metas(co_signers),
+    )
+}
+
+pub fn remove_withdrawal_destination(owner: &Pubkey, destination: Pubkey, co_signers: &[Pubkey]) -> Instruction {
+    build(
+        accounts::UpdateTreasuryPolicy {
+            config: pda::config().0,
+            policy: pda::treasury_policy().0,
+            owner: *owner,
+        },
+        instruction::RemoveWithdrawalDestination { destination },
+        co_signer_metas(co_signers),
+    )
+}
+
+pub fn set_fee_recipients(owner: &Pubkey, recipients: Vec<FeeRecipient>, co_signers: &[Pubkey]) -> Instruction {
+    build(
+        accounts::SetFeeRecipients {
+            config: pda::config().0,
+            revenue_share: pda::revenue_share().0,
+            policy: pda::treasury_policy().0,
+            owner: *owner,
+            system_program: system_program::ID,
+        },
+        instruction::SetFeeRecipients { recipients },
+        co_signer_metas(co_signers),
+    )
+}
+
+/// `recipients` must list the `RevenueShare` recipients in order
+pub fn distribute_fees(recipients: &[Pubkey]) -> Instruction {
+    build(
+        accounts::DistributeFees {
+            revenue_share: pda::revenue_share().0,
+            treasury: pda::treasury().0,
+            policy: pda::treasury_policy().0,
+        },
+        instruction::DistributeFees {},
+        recipients
+            .iter()
+            .map(|recipient| AccountMeta::new(*recipient, false))
+            .collect::<Vec<_>>(),
+    )
+}
+
+pub fn register_integrator(
+    owner: &Pubkey,
+    authority: &Pubkey,
+    payout: Pubkey,
+    share_bps: u16,
+    co_signers: &[Pubkey],
+) -> Instruction {
+    build(
+        accounts::RegisterIntegrator {
+            integrator: pda::integrator(authority).0,
+            authority: *authority,
+            config: pda::config().0,
+            owner: *owner,
+            system_program: system_program::ID,
+        },
+        instruction::RegisterIntegrator { payout, share_bps },
+        co_signer_metas(co_signers),
+    )
+}
+
+pub fn update_integrator(
+    owner: &Pubkey,
+    authority: &Pubkey,
+    payout: Option<Pubkey>,
+    share_bps: Option<u16>,
+    is_active: Option<bool>,
+    co_signers: &[Pubkey],
+) -> Instruction {
+    build(
+        accounts::UpdateIntegrator {
+            integrator: pda::integrator(authority).0,
+            config: pda::config().0,
+            owner: *owner,
+        },
+        instruction::UpdateIntegrator {
+            payout,
+            share_bps,
+            is_active,
+        },
+        co_signer_metas(co_signers),
+    )
+}
+
+pub fn migrate_config(owner: &Pubkey) -> Instruction {
+    build(
+        accounts::MigrateConfig {
+            config: pda::config().0,
+            owner: *owner,
+            system_program: system_program::ID,
+        },
+        instruction::MigrateConfig {},
+        [],
+    )
+}
+
+pub fn migrate_treasury(owner: &Pubkey, co_signers: &[Pubkey]) -> Instruction {
+    build(
+        accounts::MigrateTreasury {
+            config: pda::config().0,
+            treasury: pda::treasury().0,
+            policy: pda::treasury_policy().0,
+            owner: *owner,
+            system_program: system_program::ID,
+        },
+        instruction::MigrateTreasury {},
+        co_signer_metas(co_signers),
+    )
+}
+
+pub fn migrate_referral(payer: &Pubkey, referrer: &Pubkey) -> Instruction {
+    build(
+        accounts::MigrateReferral {
+            referral_account: pda::referral(referrer).0,
+            payer: *payer,
+            system_program: system_program::ID,
+        },
+        instruction::MigrateReferral {},
+        [],
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::Discriminator;
+
+    fn keys(ix: &Instruction) -> Vec<Pubkey> {
+        ix.accounts.iter().map(|meta| meta.pubkey).collect()
+    }
+
+    fn args<T: AnchorDeserialize + Discriminator>(ix: &Instruction) -> T {
+        assert_eq!(ix.data[..8], T::DISCRIMINATOR);
+        T::try_from_slice(&ix.data[8..]).unwrap()
+    }
+
+    /// Every optional fee account filled in, paid by `payer`
+    fn all_fees(
+        payer: &Pubkey,
+        referrer: &Pubkey,
+        authority: &Pubkey,
+        payout: &Pubkey,
+    ) -> FeeOptions {
+        FeeOptions {
+            referral: Some((*referrer, "AURUM123".to_string())),
+            promo_code: Some("LAUNCH".to_string()),
+            integrator: Some((*authority, *payout)),
+            fee_exemption: Some(*payer),
+            credits: Some((*payer, OperationKind::Create)),
+            stake: Some(*payer),
+        }
+    }
+
+    #[test]
+    fn unused_fee_accounts_are_the_program_id() {
+        let payer = Pubkey::new_unique();
+        let mint = Pubkey::new_unique();
+        let ix = create_token(&payer, &mint, 9, &FeeOptions::default());
+
+        assert_eq!(ix.program_id, ID);
+        assert_eq!(keys(&ix)[..3], [mint, pda::config().0, pda::treasury().0]);
+        for meta in &ix.accounts[3..11] {
+            assert_eq!(*meta, AccountMeta::new_readonly(ID, false));
+        }
+        assert_eq!(ix.accounts[0], AccountMeta::new(mint, true));
+        assert_eq!(ix.accounts[11], AccountMeta::new(payer, true));
+        assert_eq!(
+            keys(&ix)[12..],
+            [token::ID, system_program::ID, sysvar::rent::ID]
+        );
+
+        let data: instruction::CreateToken = args(&ix);
+        assert_eq!(data.decimals, 9);
+        assert_eq!(data.referral_code, None);
+    }
+
+    #[test]
+    fn fee_accounts_follow_the_treasury() {
+        let payer = Pubkey::new_unique();
+        let (referrer, authority, payout) = (
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        );
+        let fees = all_fees(&payer, &referrer, &authority, &payout);
+        let expected = [
+            pda::referral(&referrer).0,
+            referrer,
+            pda::promo_code("LAUNCH").0,
+            pda::integrator(&authority).0,
+            payout,
+            pda::fee_exemption(&payer).0,
+            pda::credit_account(&payer, OperationKind::Create).0,
+            pda::stake_account(&payer).0,
+        ];
+
+        let mint = Pubkey::new_unique();
+        let ix = create_token(&payer, &mint, 6, &fees);
+        assert_eq!(keys(&ix)[3..11], expected);
+        // Only the stake account is read-only
+        let writable: Vec<bool> = ix.accounts[3..11]
+            .iter()
+            .map(|meta| meta.is_writable)
+            .collect();
+        assert_eq!(writable, [true, true, true, true, true, true, true, false]);
+        assert!(ix.accounts[3..11].iter().all(|meta| !meta.is_signer));
+        let data: instruction::CreateToken = args(&ix);
+        assert_eq!(data.referral_code.as_deref(), Some("AURUM123"));
+
+        // Burns carry the same block after their own accounts
+        let ix = burn_tokens(&payer, &mint, 1_000, Some(4), &fees);
+        assert_eq!(
+            keys(&ix)[..6],
+            [
+                mint,
+                get_associated_token_address(&payer, &mint),
+                pda::burn_stats(&mint).0,
+                pda::burn_receipt(&mint, 4).0,
+                pda::config().0,
+                pda::treasury().0,
+            ]
+        );
+        assert_eq!(keys(&ix)[6..14], expected);
+        assert_eq!(ix.accounts[14], AccountMeta::new(payer, true));
+        let data: instruction::BurnTokens = args(&ix);
+        assert_eq!(data.amount, 1_000);
+    }
+
+    #[test]
+    fn quote_fee_passes_the_payer_and_read_only_fee_accounts() {
+        let payer = Pubkey::new_unique();
+        let (referrer, authority, payout) = (
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        );
+        let ix = quote_fee(
+            &payer,
+            OperationKind::Create,
+            0,
+            &all_fees(&payer, &referrer, &authority, &payout),
+        );
+
+        assert_eq!(
+            keys(&ix),
+            [
+                pda::config().0,
+                payer,
+                pda::referral(&referrer).0,
+                pda::promo_code("LAUNCH").0,
+                pda::integrator(&authority).0,
+                pda::fee_exemption(&payer).0,
+                pda::stake_account(&payer).0,
+                pda::credit_account(&payer, OperationKind::Create).0,
+            ]
+        );
+        assert!(ix
+            .accounts
+            .iter()
+            .all(|meta| !meta.is_writable && !meta.is_signer));
+        let data: instruction::QuoteFee = args(&ix);
+        assert_eq!(data.operation, OperationKind::Create);
+        assert_eq!(data.referral_code.as_deref(), Some("AURUM123"));
+    }
+
+    #[test]
+    fn co_signers_are_appended_as_signers() {
+        let owner = Pubkey::new_unique();
+        let co_signers = [Pubkey::new_unique(), Pubkey::new_unique()];
+        let ix = update_promo_code(
+            &owner,
+            "LAUNCH",
+            Some(1_000),
+            None,
+            None,
+            None,
+            None,
+            None,
+            true,
+            false,
+            &co_signers,
+        );
+
+        assert_eq!(
+            keys(&ix)[..3],
+            [pda::promo_code("LAUNCH").0, pda::config().0, owner]
+        );
+        assert!(ix.accounts[2].is_signer);
+        assert_eq!(
+            ix.accounts[3..],
+            [
+                AccountMeta::new_readonly(co_signers[0], true),
+                AccountMeta::new_readonly(co_signers[1], true),
+            ]
+        );
+        let data: instruction::UpdatePromoCode = args(&ix);
+        assert_eq!(data.discount_bps, Some(1_000));
+        assert!(data.clear_expiry && !data.clear_max_uses);
+
+        // A single-owner deactivation has no remaining accounts
+        let referrer = Pubkey::new_unique();
+        assert_eq!(
+            deactivate_referral(&referrer, &referrer, &[])
+                .accounts
+                .len(),
+            3
+        );
+    }
+}
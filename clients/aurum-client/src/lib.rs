@@ -0,0 +1,20 @@
+//! Rust client for the AURUM hybrid pricing program.
+//!
+//! - [`pda`] derives every program address
+//! - [`instructions`] builds an `Instruction` for every program instruction,
+//!   with accounts in on-chain order and optional fee accounts filled in
+//! - [`accounts`] decodes `Config`, `Treasury` and `ReferralAccount`, including
+//!   layouts that have not been migrated yet
+//! - [`quote`] computes fees with the same math as the program
+//! - [`batch`] splits `batch_lock` entries into transaction-sized chunks
+//! - [`events`] parses program events out of transaction logs
+
+pub mod accounts;
+pub mod batch;
+pub mod events;
+pub mod instructions;
+pub mod pda;
+pub mod quote;
+
+pub use aurum_hybrid_pricing as program;
+pub use aurum_hybrid_pricing::ID;
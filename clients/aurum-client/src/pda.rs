@@ -0,0 +1,119 @@
+//! Program derived addresses
+
+use anchor_lang::prelude::Pubkey;
+use anchor_spl::associated_token::get_associated_token_address;
+use aurum_hybrid_pricing::constants::*;
+use aurum_hybrid_pricing::state::OperationKind;
+use aurum_hybrid_pricing::ID;
+
+/// `["config"]`
+pub fn config() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[CONFIG_SEED], &ID)
+}
+
+/// `["treasury"]`
+pub fn treasury() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[TREASURY_SEED], &ID)
+}
+
+/// `["referral", referrer]`
+pub fn referral(referrer: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[REFERRAL_SEED, referrer.as_ref()], &ID)
+}
+
+/// `["revenue_share"]`
+pub fn revenue_share() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[REVENUE_SHARE_SEED], &ID)
+}
+
+/// `["integrator", authority]`
+pub fn integrator(authority: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[INTEGRATOR_SEED, authority.as_ref()], &ID)
+}
+
+/// `["treasury_policy"]`
+pub fn treasury_policy() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[TREASURY_POLICY_SEED], &ID)
+}
+
+/// `["proposal", id]`
+pub fn proposal(id: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[PROPOSAL_SEED, &id.to_le_bytes()], &ID)
+}
+
+/// `["promo", code]`
+pub fn promo_code(code: &str) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[PROMO_SEED, code.as_bytes()], &ID)
+}
+
+/// `["fee_exemption", wallet]`
+pub fn fee_exemption(wallet: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[FEE_EXEMPTION_SEED, wallet.as_ref()], &ID)
+}
+
+/// `["credits", owner, operation]`
+pub fn credit_account(owner: &Pubkey, operation: OperationKind) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[CREDIT_SEED, owner.as_ref(), &[operation as u8]], &ID)
+}
+
+/// `["staking_pool"]`
+pub fn staking_pool() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[STAKING_POOL_SEED], &ID)
+}
+
+/// `["stake", owner]`
+pub fn stake_account(owner: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[STAKE_SEED, owner.as_ref()], &ID)
+}
+
+/// `["lock", mint, locker, lock_id]`
+pub fn lock_record(mint: &Pubkey, locker: &Pubkey, lock_id: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[LOCK_SEED, mint.as_ref(), locker.as_ref(), &lock_id.to_le_bytes()],
+        &ID,
+    )
+}
+
+/// `["burn_stats", mint]`
+pub fn burn_stats(mint: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[BURN_STATS_SEED, mint.as_ref()], &ID)
+}
+
+/// `["burn_receipt", mint, sequence]`
+pub fn burn_receipt(mint: &Pubkey, sequence: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[BURN_RECEIPT_SEED, mint.as_ref(), &sequence.to_le_bytes()], &ID)
+}
+
+/// `["burn_schedule", mint, authority, schedule_id]`
+pub fn burn_schedule(mint: &Pubkey, authority: &Pubkey, schedule_id: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[BURN_SCHEDULE_SEED, mint.as_ref(), authority.as_ref(), &schedule_id.to_le_bytes()],
+        &ID,
+    )
+}
+
+/// `["distributor", authority, distributor_id]`
+pub fn distributor(authority: &Pubkey, distributor_id: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[DISTRIBUTOR_SEED, authority.as_ref(), &distributor_id.to_le_bytes()],
+        &ID,
+    )
+}
+
+/// `["claim_status", distributor, index]`
+pub fn claim_status(distributor: &Pubkey, index: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[CLAIM_STATUS_SEED, distributor.as_ref(), &index.to_le_bytes()],
+        &ID,
+    )
+}
+
+/// Treasury-owned escrow holding locked tokens of `mint`
+pub fn escrow(mint: &Pubkey) -> Pubkey {
+    get_associated_token_address(&treasury().0, mint)
+}
+
+/// Staking pool vault holding staked tokens of `mint`
+pub fn staking_vault(mint: &Pubkey) -> Pubkey {
+    get_associated_token_address(&staking_pool().0, mint)
+}
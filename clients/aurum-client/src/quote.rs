@@ -0,0 +1,140 @@
+//! Fee quotes, computed locally with the program's own `compute_fee` or on
+//! chain through the `quote_fee` instruction; both match what a paid
+//! instruction charges.
+
+use anchor_lang::prelude::*;
+use aurum_hybrid_pricing::fees::compute_fee;
+use aurum_hybrid_pricing::state::{Config, OperationKind};
+
+pub use aurum_hybrid_pricing::fees::{FeeModifiers, FeeQuote};
+
+/// Base fee for `operation`. `recipients` is the multisend recipient count,
+/// or `max_num_nodes` for `create_distributor`; other operations ignore it.
+pub fn base_fee(config: &Config, operation: OperationKind, recipients: u64) -> Result<u64> {
+    config.base_fee(operation, recipients)
+}
+
+/// Quote `base_fee` for `operation` at time `now`. `modifiers.referral` is
+/// the referral account together with the code that will be passed to the
+/// instruction. Fails with the same error the instruction would.
+pub fn quote_fee(
+    config: &Config,
+    operation: OperationKind,
+    base_fee: u64,
+    modifiers: FeeModifiers,
+    now: i64,
+) -> Result<FeeQuote> {
+    compute_fee(config, operation, base_fee, modifiers, now)
+}
+
+/// Decode the return data of a simulated `quote_fee` instruction
+pub fn decode_quote(return_data: &[u8]) -> Result<FeeQuote> {
+    Ok(FeeQuote::try_from_slice(return_data)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aurum_hybrid_pricing::constants::*;
+    use aurum_hybrid_pricing::migration::ConfigV0;
+    use aurum_hybrid_pricing::state::{Integrator, PromoCode};
+
+    const NOW: i64 = 1_700_000_000;
+
+    fn config() -> Config {
+        ConfigV0 {
+            owner: Pubkey::new_unique(),
+            create_fee: 100_000_000,
+            lock_fee: 50_000_000,
+            burn_fee: 20_000_000,
+            referral_discount: 10_000_000,
+            referral_rebate: 5_000_000,
+            premium_anchor_price: 0,
+            bump: 255,
+        }
+        .into()
+    }
+
+    #[test]
+    fn matches_the_program_fee_math() {
+        let config = config();
+        let promo = PromoCode {
+            version: PromoCode::VERSION,
+            code: "LAUNCH".to_string(),
+            discount_bps: 2_500,
+            operations: PROMO_OP_ALL,
+            allow_referral: true,
+            expires_at: None,
+            max_uses: None,
+            uses: 0,
+            is_active: true,
+            bump: 255,
+            reserved: [0; ACCOUNT_RESERVED_BYTES],
+        };
+        let integrator = Integrator {
+            version: Integrator::VERSION,
+            authority: Pubkey::new_unique(),
+            payout: Pubkey::new_unique(),
+            share_bps: 1_000,
+            total_volume: 0,
+            total_earned: 0,
+            operations: 0,
+            is_active: true,
+            bump: 255,
+            reserved: [0; ACCOUNT_RESERVED_BYTES],
+        };
+        let modifier_sets = [
+            FeeModifiers::default(),
+            FeeModifiers {
+                promo: Some(&promo),
+                ..Default::default()
+            },
+            FeeModifiers {
+                promo: Some(&promo),
+                integrator: Some(&integrator),
+                ..Default::default()
+            },
+        ];
+
+        for operation in [
+            OperationKind::Create,
+            OperationKind::Lock,
+            OperationKind::Burn,
+            OperationKind::Multisend,
+        ] {
+            let base = base_fee(&config, operation, 10).unwrap();
+            assert_eq!(base, config.base_fee(operation, 10).unwrap());
+            for modifiers in modifier_sets {
+                let local = quote_fee(&config, operation, base, modifiers, NOW).unwrap();
+                let program = compute_fee(&config, operation, base, modifiers, NOW).unwrap();
+                assert_eq!(local, program);
+            }
+        }
+
+        let quote = quote_fee(
+            &config,
+            OperationKind::Create,
+            100_000_000,
+            modifier_sets[2],
+            NOW,
+        )
+        .unwrap();
+        assert_eq!(quote.promo_discount, 25_000_000);
+        assert_eq!(quote.final_fee, 75_000_000);
+        assert_eq!(quote.integrator_share, 7_500_000);
+    }
+
+    #[test]
+    fn decodes_quote_return_data() {
+        let quote = FeeQuote {
+            base_fee: 100,
+            promo_discount: 25,
+            final_fee: 75,
+            integrator_share: 5,
+            treasury_fee: 70,
+            ..FeeQuote::default()
+        };
+        assert_eq!(decode_quote(&quote.try_to_vec().unwrap()).unwrap(), quote);
+        assert!(decode_quote(&[1, 2, 3]).is_err());
+    }
+}
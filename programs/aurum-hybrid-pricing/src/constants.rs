@@ -9,13 +9,113 @@ pub const TREASURY_SEED: &[u8] = b"treasury";
 /// Referral account PDA seed
 pub const REFERRAL_SEED: &[u8] = b"referral";
 
+/// Revenue share (fee splitter) PDA seed
+pub const REVENUE_SHARE_SEED: &[u8] = b"revenue_share";
+
+/// Integrator registry PDA seed
+pub const INTEGRATOR_SEED: &[u8] = b"integrator";
+
+/// Treasury withdrawal policy PDA seed
+pub const TREASURY_POLICY_SEED: &[u8] = b"treasury_policy";
+
+/// Multisig proposal PDA seed
+pub const PROPOSAL_SEED: &[u8] = b"proposal";
+
+/// Lock record PDA seed
+pub const LOCK_SEED: &[u8] = b"lock";
+
+/// Admin promo code PDA seed
+pub const PROMO_SEED: &[u8] = b"promo";
+
+/// Per-mint burn statistics PDA seed
+pub const BURN_STATS_SEED: &[u8] = b"burn_stats";
+
+/// Burn receipt PDA seed
+pub const BURN_RECEIPT_SEED: &[u8] = b"burn_receipt";
+
+/// Scheduled burn PDA seed
+pub const BURN_SCHEDULE_SEED: &[u8] = b"burn_schedule";
+
+/// Merkle distributor PDA seed
+pub const DISTRIBUTOR_SEED: &[u8] = b"distributor";
+
+/// Merkle claim status PDA seed
+pub const CLAIM_STATUS_SEED: &[u8] = b"claim_status";
+
+/// Partner fee exemption PDA seed
+pub const FEE_EXEMPTION_SEED: &[u8] = b"fee_exemption";
+
+/// Seed for prepaid credit PDA (per owner and operation)
+pub const CREDIT_SEED: &[u8] = b"credits";
+
+/// Seed for the staking pool PDA, which owns the staking vault
+pub const STAKING_POOL_SEED: &[u8] = b"staking_pool";
+
+/// Seed for stake account PDA (per owner)
+pub const STAKE_SEED: &[u8] = b"stake";
+
 /// Default pricing in lamports
 pub const DEFAULT_CREATE_FEE: u64 = 600_000_000; // 0.6 SOL
 pub const DEFAULT_LOCK_FEE: u64 = 300_000_000; // 0.3 SOL
 pub const DEFAULT_BURN_FEE: u64 = 150_000_000; // 0.15 SOL
 pub const DEFAULT_REFERRAL_DISCOUNT: u64 = 100_000_000; // 0.1 SOL
 pub const DEFAULT_REFERRAL_REBATE: u64 = 50_000_000; // 0.05 SOL
+pub const DEFAULT_MULTISEND_FLAT_FEE: u64 = 50_000_000; // 0.05 SOL
+pub const DEFAULT_MULTISEND_FEE_PER_RECIPIENT: u64 = 1_000_000; // 0.001 SOL
 pub const DEFAULT_PREMIUM_ANCHOR_PRICE: u64 = 1_200_000_000; // 1.2 SOL (display only)
 
+/// Zeroed padding at the end of every account, reserved for future fields
+pub const ACCOUNT_RESERVED_BYTES: usize = 64;
+
 /// Referral code length
 pub const REFERRAL_CODE_LENGTH: usize = 8;
+
+/// Promo code maximum length
+pub const PROMO_CODE_MAX_LENGTH: usize = 16;
+
+/// Basis point denominator (10_000 = 100%)
+pub const BPS_DENOMINATOR: u64 = 10_000;
+
+/// Promo code operation flags (see `OperationKind::flag`)
+pub const PROMO_OP_CREATE: u8 = 1 << 0;
+pub const PROMO_OP_LOCK: u8 = 1 << 1;
+pub const PROMO_OP_BURN: u8 = 1 << 2;
+pub const PROMO_OP_MULTISEND: u8 = 1 << 3;
+pub const PROMO_OP_ALL: u8 = PROMO_OP_CREATE | PROMO_OP_LOCK | PROMO_OP_BURN | PROMO_OP_MULTISEND;
+
+/// Maximum number of revenue share recipients
+pub const MAX_FEE_RECIPIENTS: usize = 8;
+
+/// Maximum integrator fee share (50%)
+pub const MAX_INTEGRATOR_SHARE_BPS: u64 = 5_000;
+
+/// Maximum number of multisig admin signers
+pub const MAX_ADMIN_SIGNERS: usize = 5;
+
+/// Maximum number of allowlisted withdrawal destinations
+pub const MAX_WITHDRAWAL_DESTINATIONS: usize = 8;
+
+/// Maximum number of entries in one `batch_lock` instruction
+pub const MAX_BATCH_LOCK_ENTRIES: usize = 25;
+
+/// Maximum number of recipients in one `multisend` instruction
+pub const MAX_MULTISEND_RECIPIENTS: usize = 20;
+
+/// Maximum number of token accounts in one `close_dust_accounts` instruction
+pub const MAX_DUST_ACCOUNTS: usize = 20;
+
+/// Largest balance, in base units, that `close_dust_accounts` burns without
+/// charging `burn_fee`; bigger balances must go through `burn_all_and_close`
+pub const MAX_DUST_AMOUNT: u64 = 1_000;
+
+/// Maximum Merkle proof length accepted by `claim` (trees of up to 2^20 leaves)
+pub const MAX_MERKLE_PROOF_LEN: usize = 20;
+
+/// Number of staking discount tiers in `Config`
+pub const MAX_STAKE_TIERS: usize = 4;
+
+/// Highest staking tier discount in basis points (50%)
+pub const MAX_STAKE_DISCOUNT_BPS: u64 = 5_000;
+
+/// Seconds tokens must stay staked before they count toward a discount tier
+pub const MIN_STAKE_AGE: i64 = 86_400; // 1 day
@@ -19,4 +19,166 @@ pub enum AurumError {
     
     #[msg("Invalid fee amount")]
     InvalidFeeAmount,
+
+    #[msg("Referral code has been deactivated")]
+    ReferralInactive,
+
+    #[msg("Referral code has expired")]
+    ReferralExpired,
+
+    #[msg("Referral code has reached its maximum number of uses")]
+    ReferralUsageExceeded,
+
+    #[msg("Invalid expiry - must be in the future")]
+    InvalidExpiry,
+
+    #[msg("Invalid promo code parameters")]
+    InvalidPromoCode,
+
+    #[msg("Promo code has been deactivated")]
+    PromoCodeInactive,
+
+    #[msg("Promo code has expired")]
+    PromoCodeExpired,
+
+    #[msg("Promo code has reached its maximum number of uses")]
+    PromoCodeUsageExceeded,
+
+    #[msg("Promo code does not apply to this operation")]
+    PromoCodeNotApplicable,
+
+    #[msg("Promo code cannot be combined with a referral code")]
+    PromoReferralNotAllowed,
+
+    #[msg("Invalid fee recipients - shares must sum to 10000 bps")]
+    InvalidFeeRecipients,
+
+    #[msg("Recipient accounts do not match the revenue share configuration")]
+    FeeRecipientMismatch,
+
+    #[msg("Treasury balance is too low")]
+    InsufficientTreasuryBalance,
+
+    #[msg("Invalid or inactive integrator")]
+    InvalidIntegrator,
+
+    #[msg("Integrator fee share exceeds the maximum")]
+    InvalidIntegratorShare,
+
+    #[msg("Account already uses the current layout")]
+    AlreadyMigrated,
+
+    #[msg("Account data does not match any known layout")]
+    InvalidAccountLayout,
+
+    #[msg("Invalid multisig configuration")]
+    InvalidMultisig,
+
+    #[msg("Not enough admin signers approved this action")]
+    MultisigThresholdNotMet,
+
+    #[msg("Multisig is not enabled")]
+    MultisigDisabled,
+
+    #[msg("Signer is not a multisig admin")]
+    NotAdminSigner,
+
+    #[msg("Signer already approved this proposal")]
+    AlreadyApproved,
+
+    #[msg("Proposal has already been executed")]
+    ProposalExecuted,
+
+    #[msg("Proposal is stale - the admin signer set has changed")]
+    ProposalStale,
+
+    #[msg("Withdrawal destination does not match")]
+    InvalidDestination,
+
+    #[msg("Withdrawal destination is not on the allowlist")]
+    DestinationNotAllowed,
+
+    #[msg("Withdrawal exceeds the per-epoch cap")]
+    WithdrawalCapExceeded,
+
+    #[msg("Withdrawal cooldown has not elapsed")]
+    WithdrawalCooldown,
+
+    #[msg("Invalid treasury policy")]
+    InvalidTreasuryPolicy,
+
+    #[msg("Pool account is not a recognised AMM layout")]
+    UnsupportedPool,
+
+    #[msg("Mint is not the LP mint of the given pool")]
+    LpMintMismatch,
+
+    #[msg("Tokens are still locked")]
+    StillLocked,
+
+    #[msg("Invalid batch - check entry count, amounts and lock record accounts")]
+    InvalidBatch,
+
+    #[msg("Invalid vesting schedule - must end after the unlock time")]
+    InvalidVestingSchedule,
+
+    #[msg("Recipient token account does not exist")]
+    MissingTokenAccount,
+
+    #[msg("Invalid distributor parameters")]
+    InvalidDistributor,
+
+    #[msg("Invalid Merkle proof")]
+    InvalidProof,
+
+    #[msg("Distributor has been clawed back")]
+    DistributorClosed,
+
+    #[msg("Clawback is not allowed before the clawback time")]
+    ClawbackTooEarly,
+
+    #[msg("Invalid burn schedule parameters")]
+    InvalidBurnSchedule,
+
+    #[msg("No scheduled burn is due")]
+    NothingDue,
+
+    #[msg("Invalid fee exemption - check the wallet and discount")]
+    InvalidFeeExemption,
+
+    #[msg("Fee exemption has expired")]
+    FeeExemptionExpired,
+
+    #[msg("Credits can only be bought for create, lock or burn, at least one at a time")]
+    InvalidCreditPurchase,
+
+    #[msg("Invalid credit account - check the owner and operation")]
+    InvalidCreditAccount,
+
+    #[msg("No credits left")]
+    InsufficientCredits,
+
+    #[msg("Credit discount and refund fee cannot exceed 10000 basis points")]
+    InvalidCreditPricing,
+
+    #[msg("Stake tiers must have increasing minimum stakes and discounts of at most 5000 basis points")]
+    InvalidStakeTiers,
+
+    #[msg("Invalid stake amount")]
+    InvalidStakeAmount,
+
+    #[msg("Invalid stake account - it must belong to the payer")]
+    InvalidStakeAccount,
+
+    #[msg("Unstaked tokens are still cooling down")]
+    StakeCoolingDown,
+
+    #[msg("Token account balance exceeds the dust threshold")]
+    BalanceNotDust,
+
+    #[msg("Burn schedule vault cannot cover one interval")]
+    BurnScheduleUnderfunded,
+
+    #[msg("Referral, promo code, exemption and staking discounts do not apply to credit payments")]
+    CreditDiscountNotAllowed,
 }
@@ -1,10 +1,14 @@
 use anchor_lang::prelude::*;
 
+use crate::state::OperationKind;
+
 #[event]
 pub struct FeeCollected {
     pub payer: Pubkey,
     pub amount: u64,
-    pub fee_type: String,
+    pub fee_type: OperationKind,
+    pub exemption_discount: Option<u64>,
+    pub paid_with_credit: bool,
     pub timestamp: i64,
 }
 
@@ -17,6 +21,14 @@ pub struct ReferralUsed {
     pub timestamp: i64,
 }
 
+#[event]
+pub struct PromoCodeUsed {
+    pub code: String,
+    pub payer: Pubkey,
+    pub discount_amount: u64,
+    pub timestamp: i64,
+}
+
 #[event]
 pub struct TokenCreated {
     pub mint: Pubkey,
@@ -35,6 +47,72 @@ pub struct TokensLocked {
     pub timestamp: i64,
 }
 
+#[event]
+pub struct BatchLocked {
+    pub mint: Pubkey,
+    pub locker: Pubkey,
+    pub entries: u32,
+    pub total_amount: u64,
+    pub fee_paid: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct LiquidityLocked {
+    pub lock_record: Pubkey,
+    pub amm_program: Pubkey,
+    pub pool: Pubkey,
+    pub lp_mint: Pubkey,
+    pub mint_a: Pubkey,
+    pub mint_b: Pubkey,
+    pub locker: Pubkey,
+    pub amount: u64,
+    pub unlock_time: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct TokensUnlocked {
+    pub lock_record: Pubkey,
+    pub mint: Pubkey,
+    pub beneficiary: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct TokenAccountClosed {
+    pub mint: Pubkey,
+    pub owner: Pubkey,
+    pub token_account: Pubkey,
+    pub amount_burned: u64,
+    pub rent_reclaimed: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct BurnRecorded {
+    pub mint: Pubkey,
+    pub burner: Pubkey,
+    pub amount: u64,
+    pub sequence: u64,
+    pub total_burned: u64,
+    pub burn_count: u64,
+    pub receipt: Option<Pubkey>,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct MultisendCompleted {
+    pub mint: Pubkey,
+    pub sender: Pubkey,
+    pub recipients: u32,
+    pub total_amount: u64,
+    pub atas_created: u32,
+    pub fee_paid: u64,
+    pub timestamp: i64,
+}
+
 #[event]
 pub struct TokensBurned {
     pub mint: Pubkey,
@@ -50,3 +128,175 @@ pub struct FeesWithdrawn {
     pub amount: u64,
     pub timestamp: i64,
 }
+
+#[event]
+pub struct FeesDistributed {
+    pub recipient: Pubkey,
+    pub amount: u64,
+    pub share_bps: u16,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct IntegratorFeePaid {
+    pub integrator: Pubkey,
+    pub payout: Pubkey,
+    pub volume: u64,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AdminSignersUpdated {
+    pub signers: Vec<Pubkey>,
+    pub threshold: u8,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ProposalCreated {
+    pub proposal: Pubkey,
+    pub id: u64,
+    pub proposer: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ProposalApproved {
+    pub proposal: Pubkey,
+    pub approver: Pubkey,
+    pub approvals: u8,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ProposalExecuted {
+    pub proposal: Pubkey,
+    pub executor: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct TreasuryPolicyUpdated {
+    pub allowed_destinations: Vec<Pubkey>,
+    pub epoch_cap: u64,
+    pub cooldown_seconds: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct DistributorCreated {
+    pub distributor: Pubkey,
+    pub authority: Pubkey,
+    pub mint: Pubkey,
+    pub merkle_root: [u8; 32],
+    pub total_amount: u64,
+    pub max_num_nodes: u64,
+    pub clawback_time: i64,
+    pub fee_paid: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct TokensClaimed {
+    pub distributor: Pubkey,
+    pub claimant: Pubkey,
+    pub index: u64,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct DistributorClawedBack {
+    pub distributor: Pubkey,
+    pub authority: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct BurnScheduleCreated {
+    pub schedule: Pubkey,
+    pub authority: Pubkey,
+    pub mint: Pubkey,
+    pub amount_per_interval: u64,
+    pub interval: i64,
+    pub next_burn_at: i64,
+    pub bounty_per_crank: u64,
+    pub fee_paid: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct BurnScheduleFunded {
+    pub schedule: Pubkey,
+    pub funder: Pubkey,
+    pub token_amount: u64,
+    pub bounty_lamports: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct CreditsPurchased {
+    pub owner: Pubkey,
+    pub operation: OperationKind,
+    pub credits: u64,
+    pub price: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct CreditsRefunded {
+    pub owner: Pubkey,
+    pub operation: OperationKind,
+    pub credits: u64,
+    pub refunded: u64,
+    pub refund_fee: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct TokensStaked {
+    pub owner: Pubkey,
+    pub amount: u64,
+    pub staked: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct UnstakeRequested {
+    pub owner: Pubkey,
+    pub amount: u64,
+    pub staked: u64,
+    pub available_at: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct StakeWithdrawn {
+    pub owner: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct StakeDiscountApplied {
+    pub payer: Pubkey,
+    pub stake_account: Pubkey,
+    pub staked: u64,
+    pub discount_bps: u16,
+    pub discount_amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ScheduledBurnExecuted {
+    pub schedule: Pubkey,
+    pub mint: Pubkey,
+    pub cranker: Pubkey,
+    pub amount: u64,
+    pub intervals: u64,
+    pub bounty_paid: u64,
+    pub next_burn_at: i64,
+    pub timestamp: i64,
+}
@@ -0,0 +1,1038 @@
+use anchor_lang::prelude::*;
+
+use crate::constants::*;
+use crate::errors::AurumError;
+use crate::events::*;
+use crate::state::*;
+use crate::OptionalFeeAccounts;
+
+/// Accounts involved in charging a fee, borrowed from an instruction context
+pub struct FeeAccounts<'a, 'info> {
+    pub payer: AccountInfo<'info>,
+    pub config: &'a Config,
+    pub treasury: &'a mut Account<'info, Treasury>,
+    pub optional: &'a mut OptionalFeeAccounts<'info>,
+    pub system_program: AccountInfo<'info>,
+}
+
+impl<'a, 'info> FeeAccounts<'a, 'info> {
+    pub fn new(
+        payer: &impl ToAccountInfo<'info>,
+        config: &'a Config,
+        treasury: &'a mut Account<'info, Treasury>,
+        optional: &'a mut OptionalFeeAccounts<'info>,
+        system_program: &Program<'info, System>,
+    ) -> Self {
+        Self {
+            payer: payer.to_account_info(),
+            config,
+            treasury,
+            optional,
+            system_program: system_program.to_account_info(),
+        }
+    }
+}
+
+/// Optional accounts that change the fee, as read by `compute_fee`
+#[derive(Clone, Copy, Default)]
+pub struct FeeModifiers<'a> {
+    /// Referral account together with the code passed by the payer
+    pub referral: Option<(&'a ReferralAccount, &'a str)>,
+    pub promo: Option<&'a PromoCode>,
+    pub integrator: Option<&'a Integrator>,
+    pub exemption: Option<&'a FeeExemption>,
+    pub stake: Option<&'a StakeAccount>,
+}
+
+/// Fee breakdown for one paid operation; returned by the `quote_fee` instruction
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct FeeQuote {
+    /// Fee before discounts
+    pub base_fee: u64,
+    /// Whether one prepaid credit pays the fee; no discount then applies and
+    /// `final_fee` is the credit's value
+    pub paid_with_credit: bool,
+    /// Whether a fee exemption applied (promo codes, staking tiers and
+    /// referrals are then ignored)
+    pub exemption_applied: bool,
+    /// Fee exemption discount
+    pub exemption_discount: u64,
+    /// Promo code discount
+    pub promo_discount: u64,
+    /// Staking tier discount in basis points (0 when no tier applies)
+    pub stake_discount_bps: u16,
+    /// Staking tier discount, taken off the fee left after the promo code
+    pub stake_discount: u64,
+    /// Whether the referral code matched and will be applied
+    pub referral_applied: bool,
+    /// Referral discount (0 when no referral applies), at most the fee left
+    /// after the promo and staking discounts
+    pub referral_discount: u64,
+    /// Amount paid by the payer
+    pub final_fee: u64,
+    /// Part of `final_fee` paid to the integrator
+    pub integrator_share: u64,
+    /// Part of `final_fee` paid to the treasury
+    pub treasury_fee: u64,
+    /// Rebate the treasury pays the referrer, at most `treasury_fee`
+    pub referrer_rebate: u64,
+}
+
+/// Outcome of `charge_fee`: the operation and what each party was paid
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FeeReceipt {
+    pub operation: OperationKind,
+    pub fee: FeeQuote,
+}
+
+impl FeeReceipt {
+    /// Amount paid by the payer
+    pub fn fee_paid(&self) -> u64 {
+        self.fee.final_fee
+    }
+}
+
+/// Compute the fee for `operation` at `now` without moving any funds. Fails
+/// with the same errors as `charge_fee`.
+///
+/// A fee exemption replaces promo codes, staking tiers and referrals: when one
+/// is supplied, none of them is applied and no referral rebate is paid.
+pub fn compute_fee(
+    config: &Config,
+    operation: OperationKind,
+    base_fee: u64,
+    modifiers: FeeModifiers,
+    now: i64,
+) -> Result<FeeQuote> {
+    let FeeModifiers {
+        referral,
+        promo,
+        integrator,
+        exemption,
+        stake,
+    } = modifiers;
+    let mut quote = FeeQuote {
+        base_fee,
+        ..FeeQuote::default()
+    };
+    let mut final_fee = base_fee;
+
+    if let Some(exemption) = exemption {
+        quote.exemption_discount = exemption.discount_for(base_fee, now)?;
+        quote.exemption_applied = true;
+        final_fee = final_fee
+            .checked_sub(quote.exemption_discount)
+            .ok_or(AurumError::ArithmeticOverflow)?;
+    }
+
+    // Apply admin promo code first (percentage of the base fee)
+    if let Some(promo) = promo.filter(|_| !quote.exemption_applied) {
+        quote.promo_discount = promo.discount_for(base_fee, operation, now)?;
+        final_fee = final_fee
+            .checked_sub(quote.promo_discount)
+            .ok_or(AurumError::ArithmeticOverflow)?;
+    }
+
+    if let Some(stake) = stake.filter(|_| !quote.exemption_applied) {
+        quote.stake_discount_bps = config.stake_discount_bps(stake.eligible_amount(now));
+        quote.stake_discount = ((final_fee as u128)
+            .checked_mul(quote.stake_discount_bps as u128)
+            .ok_or(AurumError::ArithmeticOverflow)?
+            / BPS_DENOMINATOR as u128) as u64;
+        final_fee -= quote.stake_discount;
+    }
+
+    if let Some((referral, code)) = referral.filter(|_| !quote.exemption_applied) {
+        if referral.code == code {
+            referral.validate_usable(now)?;
+            if let Some(promo) = promo {
+                require!(promo.allow_referral, AurumError::PromoReferralNotAllowed);
+            }
+            // The promo and stake discounts may already have taken the fee
+            // below the flat referral discount; the fee bottoms out at zero
+            quote.referral_applied = true;
+            quote.referral_discount = config.referral_discount.min(final_fee);
+            final_fee = final_fee.saturating_sub(config.referral_discount);
+            quote.referrer_rebate = config.referral_rebate;
+        }
+    }
+
+    if let Some(integrator) = integrator {
+        require!(integrator.is_active, AurumError::InvalidIntegrator);
+        quote.integrator_share = integrator.share_of(final_fee)?;
+    }
+
+    quote.final_fee = final_fee;
+    quote.treasury_fee = final_fee
+        .checked_sub(quote.integrator_share)
+        .ok_or(AurumError::ArithmeticOverflow)?;
+    // The rebate never exceeds what the treasury collected for this operation
+    quote.referrer_rebate = quote.referrer_rebate.min(quote.treasury_fee);
+    Ok(quote)
+}
+
+/// Quote for an operation paid with one prepaid credit worth `value`.
+/// Credits are already discounted, so referral, promo code, exemption and
+/// stake modifiers are rejected; an integrator still takes its share of the
+/// credit's value.
+pub fn credit_quote(base_fee: u64, value: u64, modifiers: FeeModifiers) -> Result<FeeQuote> {
+    let FeeModifiers {
+        referral,
+        promo,
+        integrator,
+        exemption,
+        stake,
+    } = modifiers;
+    require!(
+        referral.is_none() && promo.is_none() && exemption.is_none() && stake.is_none(),
+        AurumError::CreditDiscountNotAllowed
+    );
+
+    let mut integrator_share = 0;
+    if let Some(integrator) = integrator {
+        require!(integrator.is_active, AurumError::InvalidIntegrator);
+        integrator_share = integrator.share_of(value)?;
+    }
+
+    Ok(FeeQuote {
+        base_fee,
+        paid_with_credit: true,
+        final_fee: value,
+        integrator_share,
+        treasury_fee: value
+            .checked_sub(integrator_share)
+            .ok_or(AurumError::ArithmeticOverflow)?,
+        ..FeeQuote::default()
+    })
+}
+
+/// Checks shared by `charge_fee` and `quote_fee`: the referral account must be
+/// its referrer's PDA, and the fee exemption, stake and credit accounts must
+/// belong to the payer. A credit account must also pay for `operation`.
+pub fn validate_fee_accounts(
+    program_id: &Pubkey,
+    payer: &Pubkey,
+    operation: OperationKind,
+    referral_account: Option<&Account<ReferralAccount>>,
+    fee_exemption: Option<&Account<FeeExemption>>,
+    stake_account: Option<&Account<StakeAccount>>,
+    credit_account: Option<&Account<CreditAccount>>,
+) -> Result<()> {
+    if let Some(referral) = referral_account {
+        validate_referral_pda(program_id, referral)?;
+    }
+    if let Some(exemption) = fee_exemption {
+        require_keys_eq!(exemption.wallet, *payer, AurumError::InvalidFeeExemption);
+    }
+    if let Some(stake) = stake_account {
+        require_keys_eq!(stake.owner, *payer, AurumError::InvalidStakeAccount);
+    }
+    if let Some(credit) = credit_account {
+        require_keys_eq!(credit.owner, *payer, AurumError::InvalidCreditAccount);
+        require!(credit.operation == operation, AurumError::InvalidCreditAccount);
+    }
+    Ok(())
+}
+
+/// Check that a referral account is the referral PDA of its referrer
+pub fn validate_referral_pda(program_id: &Pubkey, referral: &Account<ReferralAccount>) -> Result<()> {
+    let (expected_pda, _bump) =
+        Pubkey::find_program_address(&[REFERRAL_SEED, referral.referrer.as_ref()], program_id);
+    require_keys_eq!(referral.key(), expected_pda, AurumError::InvalidReferralCode);
+    Ok(())
+}
+
+/// Charge `base_fee` for `operation`: validate the referral, apply promo code,
+/// staking tier, referral discount and integrator share as computed by
+/// `compute_fee`, move
+/// the fee, update treasury, referral, promo and integrator stats, pay the
+/// rebate and emit the fee events. Every paid instruction goes through here.
+/// With a credit account, one prepaid credit pays the fee instead, priced by
+/// `credit_quote`.
+pub fn charge_fee(
+    program_id: &Pubkey,
+    accounts: FeeAccounts,
+    operation: OperationKind,
+    base_fee: u64,
+    referral_code: Option<String>,
+) -> Result<FeeReceipt> {
+    let clock = Clock::get()?;
+    let FeeAccounts {
+        payer,
+        config,
+        treasury,
+        optional,
+        system_program,
+    } = accounts;
+    let OptionalFeeAccounts {
+        referral_account,
+        referrer,
+        promo_code,
+        integrator,
+        integrator_payout,
+        fee_exemption,
+        credit_account,
+        stake_account,
+    } = optional;
+    let referral_account = referral_account.as_mut();
+    let referrer = referrer.as_ref();
+    let promo_code = promo_code.as_mut();
+    let integrator = integrator.as_mut();
+    let integrator_payout = integrator_payout.as_ref();
+    let fee_exemption = fee_exemption.as_mut();
+    let credit_account = credit_account.as_mut();
+    let stake_account = stake_account.as_ref();
+
+    validate_fee_accounts(
+        program_id,
+        &payer.key(),
+        operation,
+        referral_account.as_deref(),
+        fee_exemption.as_deref(),
+        stake_account,
+        credit_account.as_deref(),
+    )?;
+
+    // If a referral account is provided, check it belongs to the referrer
+    if let Some(referral) = referral_account.as_deref() {
+        let referrer_ai = referrer.ok_or(AurumError::InvalidReferralCode)?;
+        require_keys_eq!(referrer_ai.key(), referral.referrer, AurumError::InvalidReferralCode);
+    }
+
+    let modifiers = FeeModifiers {
+        referral: referral_account
+            .as_deref()
+            .map(|referral| &**referral)
+            .zip(referral_code.as_deref()),
+        promo: promo_code.as_deref().map(|promo| &**promo),
+        integrator: integrator.as_deref().map(|integrator| &**integrator),
+        exemption: fee_exemption.as_deref().map(|exemption| &**exemption),
+        stake: stake_account.map(|stake| &**stake),
+    };
+    let quote = match credit_account.as_deref() {
+        Some(credit) => credit_quote(base_fee, credit.next_value()?, modifiers)?,
+        None => compute_fee(config, operation, base_fee, modifiers, clock.unix_timestamp)?,
+    };
+
+    // A credit pays out of the lamports held for it instead of the payer
+    let credit_info = match credit_account {
+        Some(credit) => {
+            credit.spend()?;
+            Some(credit.to_account_info())
+        }
+        None => None,
+    };
+
+    // Pay the integrator's share directly from the payer, or from the credit
+    if let Some(integrator) = integrator {
+        let payout = integrator_payout.ok_or(AurumError::InvalidIntegrator)?;
+        require_keys_eq!(payout.key(), integrator.payout, AurumError::InvalidIntegrator);
+
+        if quote.integrator_share > 0 {
+            pay_fee(&payer, credit_info.as_ref(), payout, &system_program, quote.integrator_share)?;
+        }
+
+        integrator.record(quote.final_fee, quote.integrator_share)?;
+
+        emit!(IntegratorFeePaid {
+            integrator: integrator.key(),
+            payout: integrator.payout,
+            volume: quote.final_fee,
+            amount: quote.integrator_share,
+            timestamp: clock.unix_timestamp,
+        });
+    }
+
+    // Transfer fee to treasury
+    pay_fee(
+        &payer,
+        credit_info.as_ref(),
+        &treasury.to_account_info(),
+        &system_program,
+        quote.treasury_fee,
+    )?;
+
+    // Update treasury
+    treasury.total_collected = treasury
+        .total_collected
+        .checked_add(quote.treasury_fee)
+        .ok_or(AurumError::ArithmeticOverflow)?;
+    treasury.record_operation(operation, quote.treasury_fee)?;
+
+    // Handle referral rebate
+    if quote.referral_applied {
+        let referral = referral_account.ok_or(AurumError::InvalidReferralCode)?;
+        let referrer_ai = referrer.ok_or(AurumError::InvalidReferralCode)?;
+        let rebate = quote.referrer_rebate;
+
+        Treasury::pay_out(&treasury.to_account_info(), referrer_ai, rebate)?;
+        treasury.total_rebated = treasury
+            .total_rebated
+            .checked_add(rebate)
+            .ok_or(AurumError::ArithmeticOverflow)?;
+
+        referral.total_referrals = referral
+            .total_referrals
+            .checked_add(1)
+            .ok_or(AurumError::ArithmeticOverflow)?;
+        referral.rebate_earned = referral
+            .rebate_earned
+            .checked_add(rebate)
+            .ok_or(AurumError::ArithmeticOverflow)?;
+
+        emit!(ReferralUsed {
+            referrer: referral.referrer,
+            referee: payer.key(),
+            discount_amount: quote.referral_discount,
+            rebate_amount: rebate,
+            timestamp: clock.unix_timestamp,
+        });
+    }
+
+    if let Some(exemption) = fee_exemption {
+        exemption.record(quote.exemption_discount)?;
+    }
+
+    // The stake account is seeds-checked by the instruction, so this event
+    // proves the tier the discount was taken from
+    if let Some(stake) = stake_account.filter(|_| quote.stake_discount_bps > 0) {
+        emit!(StakeDiscountApplied {
+            payer: payer.key(),
+            stake_account: stake.key(),
+            staked: stake.eligible_amount(clock.unix_timestamp),
+            discount_bps: quote.stake_discount_bps,
+            discount_amount: quote.stake_discount,
+            timestamp: clock.unix_timestamp,
+        });
+    }
+
+    // Record promo code usage
+    if let Some(promo) = promo_code.filter(|_| !quote.exemption_applied) {
+        promo.uses = promo.uses.checked_add(1).ok_or(AurumError::ArithmeticOverflow)?;
+
+        emit!(PromoCodeUsed {
+            code: promo.code.clone(),
+            payer: payer.key(),
+            discount_amount: quote.promo_discount,
+            timestamp: clock.unix_timestamp,
+        });
+    }
+
+    emit!(FeeCollected {
+        payer: payer.key(),
+        amount: quote.final_fee,
+        fee_type: operation,
+        exemption_discount: quote.exemption_applied.then_some(quote.exemption_discount),
+        paid_with_credit: quote.paid_with_credit,
+        timestamp: clock.unix_timestamp,
+    });
+
+    Ok(FeeReceipt {
+        operation,
+        fee: quote,
+    })
+}
+
+/// Move `amount` of a fee to `to`: from the payer's wallet, or from the credit
+/// account when a prepaid credit pays
+fn pay_fee<'info>(
+    payer: &AccountInfo<'info>,
+    credit_account: Option<&AccountInfo<'info>>,
+    to: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    amount: u64,
+) -> Result<()> {
+    if let Some(credit_account) = credit_account {
+        return CreditAccount::pay_out(credit_account, to, amount);
+    }
+
+    let cpi_context = CpiContext::new(
+        system_program.clone(),
+        anchor_lang::system_program::Transfer {
+            from: payer.clone(),
+            to: to.clone(),
+        },
+    );
+    anchor_lang::system_program::transfer(cpi_context, amount)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOW: i64 = 1_700_000_000;
+    const BASE: u64 = 100_000_000;
+
+    fn config() -> Config {
+        let mut stake_tiers = [StakeTier::default(); MAX_STAKE_TIERS];
+        stake_tiers[0] = StakeTier {
+            min_stake: 1_000,
+            discount_bps: 1_000,
+        };
+        stake_tiers[1] = StakeTier {
+            min_stake: 10_000,
+            discount_bps: 2_500,
+        };
+        Config {
+            version: Config::VERSION,
+            owner: Pubkey::new_unique(),
+            create_fee: BASE,
+            lock_fee: BASE,
+            burn_fee: BASE,
+            referral_discount: 10_000_000,
+            referral_rebate: 5_000_000,
+            premium_anchor_price: 0,
+            admin_threshold: 0,
+            admin_signers: Vec::new(),
+            admin_set_seq: 0,
+            proposal_count: 0,
+            bump: 255,
+            multisend_flat_fee: 0,
+            multisend_fee_per_recipient: 0,
+            credit_discount_bps: 0,
+            credit_refund_fee_bps: 0,
+            stake_tiers,
+            reserved: [0; ACCOUNT_RESERVED_BYTES - 60],
+        }
+    }
+
+    fn referral_account() -> ReferralAccount {
+        ReferralAccount {
+            version: ReferralAccount::VERSION,
+            referrer: Pubkey::new_unique(),
+            code: "AURUM123".to_string(),
+            total_referrals: 0,
+            rebate_earned: 0,
+            expires_at: None,
+            max_uses: None,
+            is_active: true,
+            bump: 255,
+            reserved: [0; ACCOUNT_RESERVED_BYTES],
+        }
+    }
+
+    fn promo_code(discount_bps: u16, allow_referral: bool) -> PromoCode {
+        PromoCode {
+            version: PromoCode::VERSION,
+            code: "LAUNCH".to_string(),
+            discount_bps,
+            operations: PROMO_OP_CREATE,
+            allow_referral,
+            expires_at: None,
+            max_uses: None,
+            uses: 0,
+            is_active: true,
+            bump: 255,
+            reserved: [0; ACCOUNT_RESERVED_BYTES],
+        }
+    }
+
+    fn integrator_account(share_bps: u16) -> Integrator {
+        Integrator {
+            version: Integrator::VERSION,
+            authority: Pubkey::new_unique(),
+            payout: Pubkey::new_unique(),
+            share_bps,
+            total_volume: 0,
+            total_earned: 0,
+            operations: 0,
+            is_active: true,
+            bump: 255,
+            reserved: [0; ACCOUNT_RESERVED_BYTES],
+        }
+    }
+
+    fn fee_exemption(discount_bps: u16, expires_at: Option<i64>) -> FeeExemption {
+        FeeExemption {
+            version: FeeExemption::VERSION,
+            wallet: Pubkey::new_unique(),
+            discount_bps,
+            expires_at,
+            uses: 0,
+            total_waived: 0,
+            bump: 255,
+            reserved: [0; ACCOUNT_RESERVED_BYTES],
+        }
+    }
+
+    fn stake_account(amount: u64, staked_at: i64) -> StakeAccount {
+        let mut stake = StakeAccount {
+            version: StakeAccount::VERSION,
+            owner: Pubkey::new_unique(),
+            amount: 0,
+            unstaking: 0,
+            unstake_available_at: 0,
+            bump: 255,
+            recent: 0,
+            recent_since: 0,
+            reserved: [0; ACCOUNT_RESERVED_BYTES - 16],
+        };
+        stake.add_stake(amount, staked_at).unwrap();
+        stake
+    }
+
+    fn quote_with(config: &Config, modifiers: FeeModifiers) -> FeeQuote {
+        compute_fee(config, OperationKind::Create, BASE, modifiers, NOW).unwrap()
+    }
+
+    fn credit_account(
+        owner: Pubkey,
+        operation: OperationKind,
+        credits: u64,
+        balance: u64,
+    ) -> CreditAccount {
+        CreditAccount {
+            version: CreditAccount::VERSION,
+            owner,
+            operation,
+            credits,
+            balance,
+            purchased: credits,
+            used: 0,
+            bump: 255,
+            reserved: [0; ACCOUNT_RESERVED_BYTES],
+        }
+    }
+
+    /// Wrap `value` in an `Account` owned by this program, as the runtime would
+    fn account<T>(value: &T) -> Account<'static, T>
+    where
+        T: AccountSerialize + AccountDeserialize + Owner + Clone,
+    {
+        let mut data = Vec::new();
+        value.try_serialize(&mut data).unwrap();
+        let info = AccountInfo::new(
+            Box::leak(Box::new(Pubkey::new_unique())),
+            false,
+            true,
+            Box::leak(Box::new(0)),
+            Box::leak(data.into_boxed_slice()),
+            &crate::ID,
+            false,
+            0,
+        );
+        Account::try_from(Box::leak(Box::new(info))).unwrap()
+    }
+
+    fn assert_error<T: std::fmt::Debug>(result: Result<T>, expected: AurumError) {
+        match result.unwrap_err() {
+            Error::AnchorError(err) => assert_eq!(err.error_code_number, u32::from(expected)),
+            Error::ProgramError(err) => panic!("unexpected program error {:?}", err),
+        }
+    }
+
+    #[test]
+    fn no_modifiers_charges_base_fee() {
+        let quote = quote_with(&config(), FeeModifiers::default());
+        assert_eq!(
+            quote,
+            FeeQuote {
+                base_fee: BASE,
+                final_fee: BASE,
+                treasury_fee: BASE,
+                ..FeeQuote::default()
+            }
+        );
+    }
+
+    #[test]
+    fn promo_discount_is_bps_of_base() {
+        let promo = promo_code(2_000, true);
+        let quote = quote_with(
+            &config(),
+            FeeModifiers {
+                promo: Some(&promo),
+                ..Default::default()
+            },
+        );
+        assert_eq!(quote.promo_discount, 20_000_000);
+        assert_eq!(quote.final_fee, 80_000_000);
+
+        let full = promo_code(10_000, true);
+        let quote = quote_with(
+            &config(),
+            FeeModifiers {
+                promo: Some(&full),
+                ..Default::default()
+            },
+        );
+        assert_eq!(quote.final_fee, 0);
+    }
+
+    #[test]
+    fn promo_for_other_operation_is_rejected() {
+        let promo = promo_code(2_000, true);
+        let modifiers = FeeModifiers {
+            promo: Some(&promo),
+            ..Default::default()
+        };
+        let result = compute_fee(&config(), OperationKind::Burn, BASE, modifiers, NOW);
+        assert_error(result, AurumError::PromoCodeNotApplicable);
+    }
+
+    #[test]
+    fn stake_counts_only_after_min_stake_age() {
+        let config = config();
+        let young = stake_account(10_000, NOW - MIN_STAKE_AGE + 1);
+        let quote = quote_with(
+            &config,
+            FeeModifiers {
+                stake: Some(&young),
+                ..Default::default()
+            },
+        );
+        assert_eq!(quote.stake_discount_bps, 0);
+        assert_eq!(quote.final_fee, BASE);
+
+        let aged = stake_account(10_000, NOW - MIN_STAKE_AGE);
+        let quote = quote_with(
+            &config,
+            FeeModifiers {
+                stake: Some(&aged),
+                ..Default::default()
+            },
+        );
+        assert_eq!(quote.stake_discount_bps, 2_500);
+        assert_eq!(quote.stake_discount, 25_000_000);
+        assert_eq!(quote.final_fee, 75_000_000);
+    }
+
+    #[test]
+    fn stake_tier_boundaries() {
+        let config = config();
+        for (amount, bps) in [(999, 0), (1_000, 1_000), (9_999, 1_000), (10_000, 2_500)] {
+            let stake = stake_account(amount, 0);
+            let quote = quote_with(
+                &config,
+                FeeModifiers {
+                    stake: Some(&stake),
+                    ..Default::default()
+                },
+            );
+            assert_eq!(quote.stake_discount_bps, bps, "staked {}", amount);
+        }
+    }
+
+    #[test]
+    fn stake_discount_applies_after_promo() {
+        let promo = promo_code(2_000, true);
+        let stake = stake_account(10_000, 0);
+        let modifiers = FeeModifiers {
+            promo: Some(&promo),
+            stake: Some(&stake),
+            ..Default::default()
+        };
+        let quote = quote_with(&config(), modifiers);
+        assert_eq!(quote.promo_discount, 20_000_000);
+        assert_eq!(quote.stake_discount, 20_000_000);
+        assert_eq!(quote.final_fee, 60_000_000);
+    }
+
+    #[test]
+    fn referral_discount_and_rebate() {
+        let referral = referral_account();
+        let modifiers = FeeModifiers {
+            referral: Some((&referral, "AURUM123")),
+            ..Default::default()
+        };
+        let quote = quote_with(&config(), modifiers);
+        assert!(quote.referral_applied);
+        assert_eq!(quote.referral_discount, 10_000_000);
+        assert_eq!(quote.referrer_rebate, 5_000_000);
+        assert_eq!(quote.final_fee, 90_000_000);
+        assert_eq!(quote.treasury_fee, 90_000_000);
+    }
+
+    #[test]
+    fn mismatched_referral_code_is_ignored() {
+        let referral = referral_account();
+        let modifiers = FeeModifiers {
+            referral: Some((&referral, "OTHER")),
+            ..Default::default()
+        };
+        let quote = quote_with(&config(), modifiers);
+        assert!(!quote.referral_applied);
+        assert_eq!(quote.final_fee, BASE);
+    }
+
+    #[test]
+    fn referral_discount_saturates_at_zero() {
+        let referral = referral_account();
+        let promo = promo_code(9_500, true);
+        let modifiers = FeeModifiers {
+            referral: Some((&referral, "AURUM123")),
+            promo: Some(&promo),
+            ..Default::default()
+        };
+        let quote = quote_with(&config(), modifiers);
+        // 5_000_000 left after the promo, below the 10_000_000 referral discount
+        assert_eq!(quote.referral_discount, 5_000_000);
+        assert_eq!(quote.final_fee, 0);
+        assert_eq!(quote.treasury_fee, 0);
+        assert_eq!(quote.referrer_rebate, 0);
+    }
+
+    #[test]
+    fn rebate_is_capped_at_treasury_fee() {
+        let referral = referral_account();
+        let promo = promo_code(8_500, true);
+        let modifiers = FeeModifiers {
+            referral: Some((&referral, "AURUM123")),
+            promo: Some(&promo),
+            ..Default::default()
+        };
+        let quote = quote_with(&config(), modifiers);
+        assert_eq!(quote.final_fee, 5_000_000);
+        assert_eq!(quote.referrer_rebate, 5_000_000);
+
+        let promo = promo_code(8_600, true);
+        let modifiers = FeeModifiers {
+            referral: Some((&referral, "AURUM123")),
+            promo: Some(&promo),
+            ..Default::default()
+        };
+        let quote = quote_with(&config(), modifiers);
+        assert_eq!(quote.final_fee, 4_000_000);
+        assert_eq!(quote.referrer_rebate, 4_000_000);
+    }
+
+    #[test]
+    fn promo_without_referral_rejects_referral() {
+        let referral = referral_account();
+        let promo = promo_code(2_000, false);
+        let modifiers = FeeModifiers {
+            referral: Some((&referral, "AURUM123")),
+            promo: Some(&promo),
+            ..Default::default()
+        };
+        let result = compute_fee(&config(), OperationKind::Create, BASE, modifiers, NOW);
+        assert_error(result, AurumError::PromoReferralNotAllowed);
+    }
+
+    #[test]
+    fn unusable_referral_is_rejected() {
+        let mut referral = referral_account();
+        referral.expires_at = Some(NOW);
+        let modifiers = FeeModifiers {
+            referral: Some((&referral, "AURUM123")),
+            ..Default::default()
+        };
+        let result = compute_fee(&config(), OperationKind::Create, BASE, modifiers, NOW);
+        assert_error(result, AurumError::ReferralExpired);
+    }
+
+    #[test]
+    fn integrator_share_comes_out_of_final_fee() {
+        let referral = referral_account();
+        let integrator = integrator_account(1_000);
+        let modifiers = FeeModifiers {
+            referral: Some((&referral, "AURUM123")),
+            integrator: Some(&integrator),
+            ..Default::default()
+        };
+        let quote = quote_with(&config(), modifiers);
+        assert_eq!(quote.final_fee, 90_000_000);
+        assert_eq!(quote.integrator_share, 9_000_000);
+        assert_eq!(quote.treasury_fee, 81_000_000);
+    }
+
+    #[test]
+    fn rebate_is_capped_after_integrator_share() {
+        let referral = referral_account();
+        let promo = promo_code(8_500, true);
+        let integrator = integrator_account(5_000);
+        let modifiers = FeeModifiers {
+            referral: Some((&referral, "AURUM123")),
+            promo: Some(&promo),
+            integrator: Some(&integrator),
+            ..Default::default()
+        };
+        let quote = quote_with(&config(), modifiers);
+        assert_eq!(quote.final_fee, 5_000_000);
+        assert_eq!(quote.treasury_fee, 2_500_000);
+        assert_eq!(quote.referrer_rebate, 2_500_000);
+    }
+
+    #[test]
+    fn inactive_integrator_is_rejected() {
+        let mut integrator = integrator_account(1_000);
+        integrator.is_active = false;
+        let modifiers = FeeModifiers {
+            integrator: Some(&integrator),
+            ..Default::default()
+        };
+        let result = compute_fee(&config(), OperationKind::Create, BASE, modifiers, NOW);
+        assert_error(result, AurumError::InvalidIntegrator);
+    }
+
+    #[test]
+    fn exemption_replaces_other_discounts() {
+        let referral = referral_account();
+        // Would be rejected if it were considered
+        let promo = promo_code(2_000, false);
+        let stake = stake_account(10_000, 0);
+        let exemption = fee_exemption(5_000, None);
+        let integrator = integrator_account(1_000);
+        let modifiers = FeeModifiers {
+            referral: Some((&referral, "AURUM123")),
+            promo: Some(&promo),
+            integrator: Some(&integrator),
+            exemption: Some(&exemption),
+            stake: Some(&stake),
+        };
+        let quote = quote_with(&config(), modifiers);
+        assert!(quote.exemption_applied);
+        assert_eq!(quote.exemption_discount, 50_000_000);
+        assert_eq!(quote.promo_discount, 0);
+        assert_eq!(quote.stake_discount, 0);
+        assert!(!quote.referral_applied);
+        assert_eq!(quote.referrer_rebate, 0);
+        assert_eq!(quote.final_fee, 50_000_000);
+        // The integrator still gets its share of what is paid
+        assert_eq!(quote.integrator_share, 5_000_000);
+        assert_eq!(quote.treasury_fee, 45_000_000);
+    }
+
+    #[test]
+    fn full_exemption_and_expiry() {
+        let exemption = fee_exemption(10_000, Some(NOW + 1));
+        let quote = quote_with(
+            &config(),
+            FeeModifiers {
+                exemption: Some(&exemption),
+                ..Default::default()
+            },
+        );
+        assert_eq!(quote.final_fee, 0);
+
+        let expired = fee_exemption(10_000, Some(NOW));
+        let modifiers = FeeModifiers {
+            exemption: Some(&expired),
+            ..Default::default()
+        };
+        let result = compute_fee(&config(), OperationKind::Create, BASE, modifiers, NOW);
+        assert_error(result, AurumError::FeeExemptionExpired);
+    }
+
+    #[test]
+    fn fee_accounts_must_belong_to_the_payer() {
+        let payer = Pubkey::new_unique();
+        let validate = |exemption: Option<&FeeExemption>,
+                        stake: Option<&StakeAccount>,
+                        credit: Option<&CreditAccount>| {
+            validate_fee_accounts(
+                &crate::ID,
+                &payer,
+                OperationKind::Create,
+                None,
+                exemption.map(account).as_ref(),
+                stake.map(account).as_ref(),
+                credit.map(account).as_ref(),
+            )
+        };
+
+        let mut exemption = fee_exemption(5_000, None);
+        let mut stake = stake_account(10_000, NOW);
+        let mut credit = credit_account(payer, OperationKind::Create, 1, BASE);
+        assert_error(
+            validate(Some(&exemption), None, None),
+            AurumError::InvalidFeeExemption,
+        );
+        assert_error(
+            validate(None, Some(&stake), None),
+            AurumError::InvalidStakeAccount,
+        );
+        credit.owner = Pubkey::new_unique();
+        assert_error(
+            validate(None, None, Some(&credit)),
+            AurumError::InvalidCreditAccount,
+        );
+
+        exemption.wallet = payer;
+        stake.owner = payer;
+        credit.owner = payer;
+        validate(Some(&exemption), Some(&stake), Some(&credit)).unwrap();
+
+        // Credits only pay for the operation they were bought for
+        credit.operation = OperationKind::Lock;
+        assert_error(
+            validate(None, None, Some(&credit)),
+            AurumError::InvalidCreditAccount,
+        );
+    }
+
+    #[test]
+    fn credit_quote_matches_the_spent_credit() {
+        let mut credit = credit_account(Pubkey::new_unique(), OperationKind::Create, 3, 10);
+        let quote =
+            credit_quote(BASE, credit.next_value().unwrap(), FeeModifiers::default()).unwrap();
+        assert!(quote.paid_with_credit);
+        assert_eq!(quote.final_fee, 3);
+        assert_eq!(quote.treasury_fee, 3);
+        assert_eq!(quote.integrator_share, 0);
+        assert_eq!(credit.spend().unwrap(), quote.final_fee);
+
+        // The last credit takes the rounding dust
+        credit.spend().unwrap();
+        assert_eq!(credit.next_value().unwrap(), 4);
+        assert_eq!(credit.spend().unwrap(), 4);
+        assert_error(credit.next_value(), AurumError::InsufficientCredits);
+    }
+
+    #[test]
+    fn credit_pays_the_integrator_share_of_its_value() {
+        let integrator = integrator_account(1_000);
+        let modifiers = FeeModifiers {
+            integrator: Some(&integrator),
+            ..Default::default()
+        };
+        let quote = credit_quote(BASE, 40_000_000, modifiers).unwrap();
+        assert!(quote.paid_with_credit);
+        assert_eq!(quote.final_fee, 40_000_000);
+        assert_eq!(quote.integrator_share, 4_000_000);
+        assert_eq!(quote.treasury_fee, 36_000_000);
+
+        let mut inactive = integrator_account(1_000);
+        inactive.is_active = false;
+        let modifiers = FeeModifiers {
+            integrator: Some(&inactive),
+            ..Default::default()
+        };
+        assert_error(
+            credit_quote(BASE, 40_000_000, modifiers),
+            AurumError::InvalidIntegrator,
+        );
+    }
+
+    #[test]
+    fn credit_rejects_discounts() {
+        let referral = referral_account();
+        let promo = promo_code(2_000, true);
+        let exemption = fee_exemption(5_000, None);
+        let stake = stake_account(10_000, NOW - 30 * 86_400);
+        let cases = [
+            FeeModifiers {
+                referral: Some((&referral, referral.code.as_str())),
+                ..Default::default()
+            },
+            FeeModifiers {
+                promo: Some(&promo),
+                ..Default::default()
+            },
+            FeeModifiers {
+                exemption: Some(&exemption),
+                ..Default::default()
+            },
+            FeeModifiers {
+                stake: Some(&stake),
+                ..Default::default()
+            },
+        ];
+        for modifiers in cases {
+            assert_error(
+                credit_quote(BASE, BASE, modifiers),
+                AurumError::CreditDiscountNotAllowed,
+            );
+        }
+    }
+}
@@ -1,6 +1,6 @@
 use anchor_lang::prelude::*;
 use anchor_spl::token::{self, Burn, Mint, MintTo, Token, TokenAccount};
-use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::associated_token::{self, get_associated_token_address, AssociatedToken};
 
 // Updated program ID after keypair generation (FJqGn3dLCFz95Xxg36RXUii3WiuDPtmw4iBfNLtE6vM7)
 declare_id!("FJqGn3dLCFz95Xxg36RXUii3WiuDPtmw4iBfNLtE6vM7");
@@ -9,11 +9,17 @@ pub mod constants;
 pub mod state;
 pub mod errors;
 pub mod events;
+pub mod migration;
+pub mod fees;
+pub mod lp;
+pub mod merkle;
 
 use constants::*;
 use state::*;
 use errors::*;
 use events::*;
+use migration::*;
+use fees::*;
 
 #[program]
 pub mod aurum_hybrid_pricing {
@@ -22,6 +28,7 @@ pub mod aurum_hybrid_pricing {
     /// Initialize the config account with default pricing
     pub fn initialize_config(ctx: Context<InitializeConfig>) -> Result<()> {
         let config = &mut ctx.accounts.config;
+        config.version = Config::VERSION;
         config.owner = ctx.accounts.owner.key();
         config.create_fee = DEFAULT_CREATE_FEE;
         config.lock_fee = DEFAULT_LOCK_FEE;
@@ -29,17 +36,45 @@ pub mod aurum_hybrid_pricing {
         config.referral_discount = DEFAULT_REFERRAL_DISCOUNT;
         config.referral_rebate = DEFAULT_REFERRAL_REBATE;
         config.premium_anchor_price = DEFAULT_PREMIUM_ANCHOR_PRICE;
+        config.admin_threshold = 0;
+        config.admin_signers = Vec::new();
+        config.admin_set_seq = 0;
+        config.proposal_count = 0;
         config.bump = ctx.bumps.config;
+        config.multisend_flat_fee = DEFAULT_MULTISEND_FLAT_FEE;
+        config.multisend_fee_per_recipient = DEFAULT_MULTISEND_FEE_PER_RECIPIENT;
+        config.credit_discount_bps = 0;
+        config.credit_refund_fee_bps = 0;
+        config.stake_tiers = [StakeTier::default(); MAX_STAKE_TIERS];
+        config.reserved = [0; ACCOUNT_RESERVED_BYTES - 60];
 
         let treasury = &mut ctx.accounts.treasury;
+        treasury.version = Treasury::VERSION;
         treasury.total_collected = 0;
+        treasury.total_rebated = 0;
+        treasury.total_withdrawn = 0;
+        treasury.total_distributed = 0;
+        treasury.creates = 0;
+        treasury.create_fees = 0;
+        treasury.locks = 0;
+        treasury.lock_fees = 0;
+        treasury.burns = 0;
+        treasury.burn_fees = 0;
         treasury.bump = ctx.bumps.treasury;
+        treasury.multisends = 0;
+        treasury.multisend_fees = 0;
+        treasury.reserved = [0; ACCOUNT_RESERVED_BYTES - 16];
+
+        ctx.accounts
+            .policy
+            .set_default(config.owner, ctx.bumps.policy);
 
         msg!("Config initialized with owner: {}", config.owner);
         Ok(())
     }
 
-    /// Update pricing configuration (owner only)
+    /// Update pricing configuration (owner, or admin multisig co-signers
+    /// passed as remaining accounts)
     pub fn update_pricing(
         ctx: Context<UpdatePricing>,
         create_fee: Option<u64>,
@@ -50,27 +85,290 @@ pub mod aurum_hybrid_pricing {
         premium_anchor_price: Option<u64>,
     ) -> Result<()> {
         let config = &mut ctx.accounts.config;
+        config.require_admin(&ctx.accounts.owner.to_account_info(), ctx.remaining_accounts)?;
+
+        config.apply_pricing(&PricingUpdate {
+            create_fee,
+            lock_fee,
+            burn_fee,
+            referral_discount,
+            referral_rebate,
+            premium_anchor_price,
+        });
+
+        msg!("Pricing updated by owner");
+        Ok(())
+    }
+
+    /// Update multisend pricing (owner, or admin multisig co-signers passed as
+    /// remaining accounts)
+    pub fn update_multisend_pricing(
+        ctx: Context<UpdatePricing>,
+        flat_fee: Option<u64>,
+        fee_per_recipient: Option<u64>,
+    ) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        config.require_admin(&ctx.accounts.owner.to_account_info(), ctx.remaining_accounts)?;
 
-        if let Some(fee) = create_fee {
-            config.create_fee = fee;
+        if let Some(fee) = flat_fee {
+            config.multisend_flat_fee = fee;
         }
-        if let Some(fee) = lock_fee {
-            config.lock_fee = fee;
+        if let Some(fee) = fee_per_recipient {
+            config.multisend_fee_per_recipient = fee;
         }
-        if let Some(fee) = burn_fee {
-            config.burn_fee = fee;
+
+        msg!("Multisend pricing updated by owner");
+        Ok(())
+    }
+
+    /// Update prepaid credit pricing (owner, or admin multisig co-signers
+    /// passed as remaining accounts)
+    pub fn update_credit_pricing(
+        ctx: Context<UpdatePricing>,
+        discount_bps: Option<u16>,
+        refund_fee_bps: Option<u16>,
+    ) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        config.require_admin(&ctx.accounts.owner.to_account_info(), ctx.remaining_accounts)?;
+
+        if let Some(bps) = discount_bps {
+            require!(bps as u64 <= BPS_DENOMINATOR, AurumError::InvalidCreditPricing);
+            config.credit_discount_bps = bps;
         }
-        if let Some(discount) = referral_discount {
-            config.referral_discount = discount;
+        if let Some(bps) = refund_fee_bps {
+            require!(bps as u64 <= BPS_DENOMINATOR, AurumError::InvalidCreditPricing);
+            config.credit_refund_fee_bps = bps;
         }
-        if let Some(rebate) = referral_rebate {
-            config.referral_rebate = rebate;
+
+        msg!("Credit pricing updated by owner");
+        Ok(())
+    }
+
+    /// Replace the staking discount tiers (owner, or admin multisig
+    /// co-signers passed as remaining accounts). Tiers are ordered by
+    /// increasing minimum stake; an empty list disables staking discounts.
+    pub fn set_stake_tiers(ctx: Context<UpdatePricing>, tiers: Vec<StakeTier>) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        config.require_admin(&ctx.accounts.owner.to_account_info(), ctx.remaining_accounts)?;
+
+        config.stake_tiers = StakeTier::table(&tiers)?;
+
+        msg!("Stake tiers updated: {} tiers", tiers.len());
+        Ok(())
+    }
+
+    /// Quote the fee for `operation` without charging it. The `FeeQuote` is
+    /// returned as return data, so wallets can call this through
+    /// `simulateTransaction`. `recipients` only prices multisend (recipient
+    /// count) and `create_distributor` (`max_num_nodes`). Pass the same
+    /// referral, promo code, integrator, fee exemption and stake accounts as
+    /// the paid instruction.
+    pub fn quote_fee(
+        ctx: Context<QuoteFee>,
+        operation: OperationKind,
+        recipients: u64,
+        referral_code: Option<String>,
+    ) -> Result<FeeQuote> {
+        let accounts = &ctx.accounts;
+        validate_fee_accounts(
+            ctx.program_id,
+            accounts.payer.key,
+            operation,
+            accounts.referral_account.as_ref(),
+            accounts.fee_exemption.as_ref(),
+            accounts.stake_account.as_ref(),
+            accounts.credit_account.as_ref(),
+        )?;
+
+        let base_fee = accounts.config.base_fee(operation, recipients)?;
+        let modifiers = FeeModifiers {
+            referral: accounts
+                .referral_account
+                .as_deref()
+                .zip(referral_code.as_deref()),
+            promo: accounts.promo_code.as_deref(),
+            integrator: accounts.integrator.as_deref(),
+            exemption: accounts.fee_exemption.as_deref(),
+            stake: accounts.stake_account.as_deref(),
+        };
+        let quote = match accounts.credit_account.as_deref() {
+            Some(credit) => credit_quote(base_fee, credit.next_value()?, modifiers)?,
+            None => compute_fee(
+                &accounts.config,
+                operation,
+                base_fee,
+                modifiers,
+                Clock::get()?.unix_timestamp,
+            )?,
+        };
+
+        msg!("Fee quote for {}: {} lamports", operation.name(), quote.final_fee);
+        Ok(quote)
+    }
+
+    /// Configure M-of-N admin signers for privileged actions. A threshold of 0
+    /// with no signers returns to single-owner mode.
+    pub fn set_admin_signers(
+        ctx: Context<SetAdminSigners>,
+        signers: Vec<Pubkey>,
+        threshold: u8,
+    ) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        config.require_admin(&ctx.accounts.owner.to_account_info(), ctx.remaining_accounts)?;
+        config.set_admin_signers(signers, threshold)?;
+
+        emit!(AdminSignersUpdated {
+            signers: config.admin_signers.clone(),
+            threshold,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        msg!("Admin signers updated: {}-of-{}", threshold, config.admin_signers.len());
+        Ok(())
+    }
+
+    /// Propose a privileged action for asynchronous multisig approval.
+    /// The proposer's approval is recorded immediately.
+    pub fn create_proposal(ctx: Context<CreateProposal>, action: AdminAction) -> Result<()> {
+        let clock = Clock::get()?;
+        let config = &mut ctx.accounts.config;
+        let proposer = ctx.accounts.proposer.key();
+        require!(config.is_multisig(), AurumError::MultisigDisabled);
+        require!(config.admin_signers.contains(&proposer), AurumError::NotAdminSigner);
+
+        if let AdminAction::SetAdminSigners { signers, threshold } = &action {
+            require!(
+                signers.len() <= MAX_ADMIN_SIGNERS && *threshold as usize <= signers.len(),
+                AurumError::InvalidMultisig
+            );
         }
-        if let Some(price) = premium_anchor_price {
-            config.premium_anchor_price = price;
+
+        let proposal = &mut ctx.accounts.proposal;
+        proposal.version = Proposal::VERSION;
+        proposal.id = config.proposal_count;
+        proposal.proposer = proposer;
+        proposal.action = action;
+        proposal.approvals = vec![proposer];
+        proposal.admin_set_seq = config.admin_set_seq;
+        proposal.executed = false;
+        proposal.created_at = clock.unix_timestamp;
+        proposal.bump = ctx.bumps.proposal;
+        proposal.reserved = [0; ACCOUNT_RESERVED_BYTES];
+
+        config.proposal_count = config
+            .proposal_count
+            .checked_add(1)
+            .ok_or(AurumError::ArithmeticOverflow)?;
+
+        emit!(ProposalCreated {
+            proposal: proposal.key(),
+            id: proposal.id,
+            proposer,
+            timestamp: clock.unix_timestamp,
+        });
+
+        msg!("Proposal {} created by {}", proposal.id, proposer);
+        Ok(())
+    }
+
+    /// Approve an open proposal (admin signer)
+    pub fn approve_proposal(ctx: Context<ApproveProposal>) -> Result<()> {
+        let config = &ctx.accounts.config;
+        let proposal = &mut ctx.accounts.proposal;
+        let approver = ctx.accounts.approver.key();
+
+        require!(!proposal.executed, AurumError::ProposalExecuted);
+        require!(proposal.admin_set_seq == config.admin_set_seq, AurumError::ProposalStale);
+        require!(config.admin_signers.contains(&approver), AurumError::NotAdminSigner);
+        require!(!proposal.approvals.contains(&approver), AurumError::AlreadyApproved);
+
+        proposal.approvals.push(approver);
+
+        emit!(ProposalApproved {
+            proposal: proposal.key(),
+            approver,
+            approvals: proposal.approvals.len() as u8,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        msg!("Proposal {} approved by {}", proposal.id, approver);
+        Ok(())
+    }
+
+    /// Execute a proposal once it has enough approvals (permissionless)
+    pub fn execute_proposal(ctx: Context<ExecuteProposal>) -> Result<()> {
+        let clock = Clock::get()?;
+        let proposal = &mut ctx.accounts.proposal;
+        let config = &mut ctx.accounts.config;
+
+        require!(!proposal.executed, AurumError::ProposalExecuted);
+        require!(proposal.admin_set_seq == config.admin_set_seq, AurumError::ProposalStale);
+        require!(
+            proposal.approvals.len() >= config.admin_threshold as usize,
+            AurumError::MultisigThresholdNotMet
+        );
+        proposal.executed = true;
+
+        match proposal.action.clone() {
+            AdminAction::UpdatePricing(update) => {
+                config.apply_pricing(&update);
+                msg!("Pricing updated by proposal {}", proposal.id);
+            }
+            AdminAction::WithdrawFees { amount, destination } => {
+                let destination_ai = ctx
+                    .accounts
+                    .destination
+                    .as_ref()
+                    .ok_or(AurumError::InvalidDestination)?;
+                require_keys_eq!(destination_ai.key(), destination, AurumError::InvalidDestination);
+                ctx.accounts
+                    .policy
+                    .as_mut()
+                    .ok_or(AurumError::InvalidTreasuryPolicy)?
+                    .record_withdrawal(&destination, amount, &clock)?;
+
+                Treasury::pay_out(
+                    &ctx.accounts.treasury.to_account_info(),
+                    destination_ai,
+                    amount,
+                )?;
+                let treasury = &mut ctx.accounts.treasury;
+                treasury.total_withdrawn = treasury
+                    .total_withdrawn
+                    .checked_add(amount)
+                    .ok_or(AurumError::ArithmeticOverflow)?;
+
+                emit!(FeesWithdrawn {
+                    recipient: destination,
+                    amount,
+                    timestamp: clock.unix_timestamp,
+                });
+                msg!("Fees withdrawn: {} lamports to {}", amount, destination);
+            }
+            AdminAction::SetAdminSigners { signers, threshold } => {
+                config.set_admin_signers(signers, threshold)?;
+                emit!(AdminSignersUpdated {
+                    signers: config.admin_signers.clone(),
+                    threshold,
+                    timestamp: clock.unix_timestamp,
+                });
+            }
         }
 
-        msg!("Pricing updated by owner");
+        emit!(ProposalExecuted {
+            proposal: proposal.key(),
+            executor: ctx.accounts.executor.key(),
+            timestamp: clock.unix_timestamp,
+        });
+
+        msg!("Proposal {} executed", proposal.id);
+        Ok(())
+    }
+
+    /// Cancel an unexecuted proposal and return rent to the proposer
+    pub fn cancel_proposal(ctx: Context<CancelProposal>) -> Result<()> {
+        require!(!ctx.accounts.proposal.executed, AurumError::ProposalExecuted);
+        msg!("Proposal {} cancelled", ctx.accounts.proposal.id);
         Ok(())
     }
 
@@ -82,135 +380,518 @@ pub mod aurum_hybrid_pricing {
         // Generate referral code from pubkey (first 8 chars of base58)
         let code = user.to_string()[0..8].to_string();
 
+        referral.version = ReferralAccount::VERSION;
         referral.referrer = user;
         referral.code = code.clone();
         referral.total_referrals = 0;
         referral.rebate_earned = 0;
+        referral.expires_at = None;
+        referral.max_uses = None;
+        referral.is_active = true;
         referral.bump = ctx.bumps.referral_account;
+        referral.reserved = [0; ACCOUNT_RESERVED_BYTES];
 
         msg!("Referral code created: {} for user: {}", code, user);
         Ok(())
     }
 
-    /// Create a token mint with hybrid pricing
-    pub fn create_token(
-        ctx: Context<CreateToken>,
-        decimals: u8,
-        referral_code: Option<String>,
+    /// Set expiry and usage cap on a referral code (referrer only). `None`
+    /// leaves a field unchanged; `clear_expiry` and `clear_max_uses` remove it.
+    pub fn update_referral(
+        ctx: Context<UpdateReferral>,
+        expires_at: Option<i64>,
+        max_uses: Option<u64>,
+        clear_expiry: bool,
+        clear_max_uses: bool,
     ) -> Result<()> {
-        let config = &ctx.accounts.config;
-        let clock = Clock::get()?;
-        
-        // If a referral account is provided, validate PDA and referrer linkage
-        if let Some(ref referral) = ctx.accounts.referral_account {
-            let (expected_pda, _bump) = Pubkey::find_program_address(
-                &[REFERRAL_SEED, referral.referrer.as_ref()],
-                ctx.program_id,
+        let referral = &mut ctx.accounts.referral_account;
+
+        if clear_expiry {
+            referral.expires_at = None;
+        } else if let Some(expires_at) = expires_at {
+            require!(
+                expires_at > Clock::get()?.unix_timestamp,
+                AurumError::InvalidExpiry
             );
-            require_keys_eq!(referral.key(), expected_pda, AurumError::InvalidReferralCode);
-
-            let referrer_ai = ctx
-                .accounts
-                .referrer
-                .as_ref()
-                .ok_or(AurumError::InvalidReferralCode)?;
-            require_keys_eq!(referrer_ai.key(), referral.referrer, AurumError::InvalidReferralCode);
-        }
-        
-        // Calculate fee with referral discount
-        let base_fee = config.create_fee;
-        let mut final_fee = base_fee;
-        let mut referrer_option: Option<Pubkey> = None;
-
-        if let Some(code) = referral_code {
-            // Validate and apply referral discount
-            if let Some(referral) = &ctx.accounts.referral_account {
-                if referral.code == code {
-                    final_fee = base_fee
-                        .checked_sub(config.referral_discount)
-                        .ok_or(AurumError::ArithmeticOverflow)?;
-                    referrer_option = Some(referral.referrer);
-                    
-                    msg!("Referral code applied: {}", code);
-                }
-            }
+            referral.expires_at = Some(expires_at);
+        }
+        if clear_max_uses {
+            referral.max_uses = None;
+        } else if let Some(max_uses) = max_uses {
+            referral.max_uses = Some(max_uses);
+        }
+
+        msg!("Referral code updated: {}", referral.code);
+        Ok(())
+    }
+
+    /// Deactivate a referral code (referrer, or owner / admin multisig
+    /// co-signers passed as remaining accounts)
+    pub fn deactivate_referral(ctx: Context<DeactivateReferral>) -> Result<()> {
+        let authority = ctx.accounts.authority.key();
+        if authority != ctx.accounts.referral_account.referrer {
+            ctx.accounts
+                .config
+                .require_admin(&ctx.accounts.authority.to_account_info(), ctx.remaining_accounts)?;
+        }
+        let referral = &mut ctx.accounts.referral_account;
+
+        referral.is_active = false;
+
+        msg!("Referral code deactivated: {} by {}", referral.code, authority);
+        Ok(())
+    }
+
+    /// Close a referral account and return rent to the referrer
+    pub fn close_referral(ctx: Context<CloseReferral>) -> Result<()> {
+        msg!(
+            "Referral code closed: {} for user: {}",
+            ctx.accounts.referral_account.code,
+            ctx.accounts.referrer.key()
+        );
+        Ok(())
+    }
+
+    /// Create an admin promo code (owner, or admin multisig co-signers passed as remaining accounts)
+    pub fn create_promo_code(
+        ctx: Context<CreatePromoCode>,
+        code: String,
+        discount_bps: u16,
+        operations: u8,
+        allow_referral: bool,
+        expires_at: Option<i64>,
+        max_uses: Option<u64>,
+    ) -> Result<()> {
+        ctx.accounts
+            .config
+            .require_admin(&ctx.accounts.owner.to_account_info(), ctx.remaining_accounts)?;
+        require!(
+            !code.is_empty() && code.len() <= PROMO_CODE_MAX_LENGTH,
+            AurumError::InvalidPromoCode
+        );
+        require!(
+            discount_bps as u64 <= BPS_DENOMINATOR
+                && operations != 0
+                && operations & !PROMO_OP_ALL == 0,
+            AurumError::InvalidPromoCode
+        );
+        if let Some(expires_at) = expires_at {
+            PromoCode::validate_expiry(expires_at, Clock::get()?.unix_timestamp)?;
+        }
+
+        let promo = &mut ctx.accounts.promo_code;
+        promo.version = PromoCode::VERSION;
+        promo.code = code;
+        promo.discount_bps = discount_bps;
+        promo.operations = operations;
+        promo.allow_referral = allow_referral;
+        promo.expires_at = expires_at;
+        promo.max_uses = max_uses;
+        promo.uses = 0;
+        promo.is_active = true;
+        promo.bump = ctx.bumps.promo_code;
+        promo.reserved = [0; ACCOUNT_RESERVED_BYTES];
+
+        msg!("Promo code created: {} ({} bps)", promo.code, discount_bps);
+        Ok(())
+    }
+
+    /// Update an admin promo code (owner, or admin multisig co-signers passed
+    /// as remaining accounts). `clear_expiry` and `clear_max_uses` remove the
+    /// expiry and usage cap and take precedence over `expires_at` and
+    /// `max_uses`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn update_promo_code(
+        ctx: Context<UpdatePromoCode>,
+        discount_bps: Option<u16>,
+        operations: Option<u8>,
+        allow_referral: Option<bool>,
+        expires_at: Option<i64>,
+        max_uses: Option<u64>,
+        is_active: Option<bool>,
+        clear_expiry: bool,
+        clear_max_uses: bool,
+    ) -> Result<()> {
+        ctx.accounts
+            .config
+            .require_admin(&ctx.accounts.owner.to_account_info(), ctx.remaining_accounts)?;
+        let promo = &mut ctx.accounts.promo_code;
+
+        if let Some(bps) = discount_bps {
+            require!(bps as u64 <= BPS_DENOMINATOR, AurumError::InvalidPromoCode);
+            promo.discount_bps = bps;
+        }
+        if let Some(ops) = operations {
+            require!(ops != 0 && ops & !PROMO_OP_ALL == 0, AurumError::InvalidPromoCode);
+            promo.operations = ops;
+        }
+        if let Some(allow) = allow_referral {
+            promo.allow_referral = allow;
+        }
+        if clear_expiry {
+            promo.expires_at = None;
+        } else if let Some(expires_at) = expires_at {
+            PromoCode::validate_expiry(expires_at, Clock::get()?.unix_timestamp)?;
+            promo.expires_at = Some(expires_at);
+        }
+        if clear_max_uses {
+            promo.max_uses = None;
+        } else if let Some(max_uses) = max_uses {
+            promo.max_uses = Some(max_uses);
+        }
+        if let Some(active) = is_active {
+            promo.is_active = active;
+        }
+
+        msg!("Promo code updated: {}", promo.code);
+        Ok(())
+    }
+
+    /// Close an admin promo code and return rent to the signing admin (owner,
+    /// or admin multisig co-signers passed as remaining accounts)
+    pub fn close_promo_code(ctx: Context<ClosePromoCode>) -> Result<()> {
+        ctx.accounts
+            .config
+            .require_admin(&ctx.accounts.owner.to_account_info(), ctx.remaining_accounts)?;
+        msg!("Promo code closed: {}", ctx.accounts.promo_code.code);
+        Ok(())
+    }
+
+    /// Exempt a partner wallet from part or all of every fee (owner, or admin
+    /// multisig co-signers passed as remaining accounts)
+    pub fn create_fee_exemption(
+        ctx: Context<CreateFeeExemption>,
+        wallet: Pubkey,
+        discount_bps: u16,
+        expires_at: Option<i64>,
+    ) -> Result<()> {
+        ctx.accounts
+            .config
+            .require_admin(&ctx.accounts.owner.to_account_info(), ctx.remaining_accounts)?;
+        require!(
+            discount_bps != 0 && discount_bps as u64 <= BPS_DENOMINATOR,
+            AurumError::InvalidFeeExemption
+        );
+        if let Some(expires_at) = expires_at {
+            require!(
+                expires_at > Clock::get()?.unix_timestamp,
+                AurumError::InvalidExpiry
+            );
+        }
+
+        let exemption = &mut ctx.accounts.fee_exemption;
+        exemption.version = FeeExemption::VERSION;
+        exemption.wallet = wallet;
+        exemption.discount_bps = discount_bps;
+        exemption.expires_at = expires_at;
+        exemption.uses = 0;
+        exemption.total_waived = 0;
+        exemption.bump = ctx.bumps.fee_exemption;
+        exemption.reserved = [0; ACCOUNT_RESERVED_BYTES];
+
+        msg!("Fee exemption created: {} ({} bps)", wallet, discount_bps);
+        Ok(())
+    }
+
+    /// Update a fee exemption (owner, or admin multisig co-signers passed as
+    /// remaining accounts). `clear_expiry` removes the expiry and takes
+    /// precedence over `expires_at`.
+    pub fn update_fee_exemption(
+        ctx: Context<UpdateFeeExemption>,
+        discount_bps: Option<u16>,
+        expires_at: Option<i64>,
+        clear_expiry: bool,
+    ) -> Result<()> {
+        ctx.accounts
+            .config
+            .require_admin(&ctx.accounts.owner.to_account_info(), ctx.remaining_accounts)?;
+        let exemption = &mut ctx.accounts.fee_exemption;
+
+        if let Some(bps) = discount_bps {
+            require!(
+                bps != 0 && bps as u64 <= BPS_DENOMINATOR,
+                AurumError::InvalidFeeExemption
+            );
+            exemption.discount_bps = bps;
+        }
+        if clear_expiry {
+            exemption.expires_at = None;
+        } else if let Some(expires_at) = expires_at {
+            require!(
+                expires_at > Clock::get()?.unix_timestamp,
+                AurumError::InvalidExpiry
+            );
+            exemption.expires_at = Some(expires_at);
         }
 
-        // Transfer fee from user to treasury
+        msg!("Fee exemption updated: {}", exemption.wallet);
+        Ok(())
+    }
+
+    /// Revoke a fee exemption and return rent to the signing admin (owner, or
+    /// admin multisig co-signers passed as remaining accounts)
+    pub fn close_fee_exemption(ctx: Context<CloseFeeExemption>) -> Result<()> {
+        ctx.accounts
+            .config
+            .require_admin(&ctx.accounts.owner.to_account_info(), ctx.remaining_accounts)?;
+        msg!("Fee exemption closed: {}", ctx.accounts.fee_exemption.wallet);
+        Ok(())
+    }
+
+    /// Prepay `count` fees for `operation` at the discounted credit price.
+    /// Credits are held per owner and operation; buying again tops up the
+    /// same account.
+    pub fn buy_credits(ctx: Context<BuyCredits>, operation: OperationKind, count: u64) -> Result<()> {
+        let price = ctx.accounts.config.credit_price(operation, count)?;
+
         let cpi_context = CpiContext::new(
             ctx.accounts.system_program.to_account_info(),
             anchor_lang::system_program::Transfer {
-                from: ctx.accounts.payer.to_account_info(),
-                to: ctx.accounts.treasury.to_account_info(),
+                from: ctx.accounts.owner.to_account_info(),
+                to: ctx.accounts.credit_account.to_account_info(),
             },
         );
-        anchor_lang::system_program::transfer(cpi_context, final_fee)?;
+        anchor_lang::system_program::transfer(cpi_context, price)?;
+
+        let credit_account = &mut ctx.accounts.credit_account;
+        if credit_account.version == 0 {
+            credit_account.version = CreditAccount::VERSION;
+            credit_account.owner = ctx.accounts.owner.key();
+            credit_account.operation = operation;
+            credit_account.bump = ctx.bumps.credit_account;
+            credit_account.reserved = [0; ACCOUNT_RESERVED_BYTES];
+        }
+        credit_account.add(count, price)?;
+
+        emit!(CreditsPurchased {
+            owner: credit_account.owner,
+            operation,
+            credits: count,
+            price,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        msg!("Bought {} {} credits for {} lamports", count, operation.name(), price);
+        Ok(())
+    }
+
+    /// Refund all unused credits, less `credit_refund_fee_bps`, and close the
+    /// credit account
+    pub fn refund_credits(ctx: Context<RefundCredits>) -> Result<()> {
+        let credit_account = &ctx.accounts.credit_account;
+        let balance = credit_account.balance;
+        let refund_fee = ((balance as u128)
+            .checked_mul(ctx.accounts.config.credit_refund_fee_bps as u128)
+            .ok_or(AurumError::ArithmeticOverflow)?
+            / BPS_DENOMINATOR as u128) as u64;
+        let refunded = balance
+            .checked_sub(refund_fee)
+            .ok_or(AurumError::ArithmeticOverflow)?;
 
-        // Update treasury stats
+        // The rest of the balance goes to the owner when the account closes
+        CreditAccount::pay_out(
+            &credit_account.to_account_info(),
+            &ctx.accounts.treasury.to_account_info(),
+            refund_fee,
+        )?;
         let treasury = &mut ctx.accounts.treasury;
         treasury.total_collected = treasury
             .total_collected
-            .checked_add(final_fee)
+            .checked_add(refund_fee)
             .ok_or(AurumError::ArithmeticOverflow)?;
 
-        // Handle referral rebate if applicable
-        if let (Some(referrer_pubkey), Some(referral)) = (referrer_option, &mut ctx.accounts.referral_account) {
-            let rebate = config.referral_rebate;
-            
-            // Transfer rebate to referrer
-            let seeds = &[
-                TREASURY_SEED,
-                &[treasury.bump],
-            ];
-            let signer = &[&seeds[..]];
-            
-            let cpi_context = CpiContext::new_with_signer(
-                ctx.accounts.system_program.to_account_info(),
-                anchor_lang::system_program::Transfer {
-                    from: ctx.accounts.treasury.to_account_info(),
-                    to: ctx.accounts.referrer.as_ref().unwrap().to_account_info(),
-                },
-                signer,
-            );
-            anchor_lang::system_program::transfer(cpi_context, rebate)?;
+        emit!(CreditsRefunded {
+            owner: credit_account.owner,
+            operation: credit_account.operation,
+            credits: credit_account.credits,
+            refunded,
+            refund_fee,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
 
-            // Update referral stats
-            referral.total_referrals = referral
-                .total_referrals
-                .checked_add(1)
-                .ok_or(AurumError::ArithmeticOverflow)?;
-            referral.rebate_earned = referral
-                .rebate_earned
-                .checked_add(rebate)
-                .ok_or(AurumError::ArithmeticOverflow)?;
+        msg!(
+            "Refunded {} {} credits: {} lamports",
+            credit_account.credits,
+            credit_account.operation.name(),
+            refunded
+        );
+        Ok(())
+    }
 
-            // Emit referral event
-            emit!(ReferralUsed {
-                referrer: referrer_pubkey,
-                referee: ctx.accounts.payer.key(),
-                discount_amount: config.referral_discount,
-                rebate_amount: rebate,
-                timestamp: clock.unix_timestamp,
-            });
+    /// Create the staking pool and its vault for `mint` (owner, or admin
+    /// multisig co-signers passed as remaining accounts)
+    pub fn initialize_staking(ctx: Context<InitializeStaking>, cooldown: i64) -> Result<()> {
+        ctx.accounts
+            .config
+            .require_admin(&ctx.accounts.owner.to_account_info(), ctx.remaining_accounts)?;
+        require!(cooldown >= 0, AurumError::InvalidStakeAmount);
+
+        let pool = &mut ctx.accounts.staking_pool;
+        pool.version = StakingPool::VERSION;
+        pool.mint = ctx.accounts.mint.key();
+        pool.cooldown = cooldown;
+        pool.total_staked = 0;
+        pool.total_unstaking = 0;
+        pool.bump = ctx.bumps.staking_pool;
+        pool.reserved = [0; ACCOUNT_RESERVED_BYTES];
+
+        msg!("Staking initialized for {} ({}s cooldown)", pool.mint, cooldown);
+        Ok(())
+    }
+
+    /// Stake tokens into the vault. Staked tokens count toward a discount
+    /// tier once they have been staked for `MIN_STAKE_AGE`.
+    pub fn stake(ctx: Context<Stake>, amount: u64) -> Result<()> {
+        require!(amount > 0, AurumError::InvalidStakeAmount);
+
+        let cpi_accounts = token::Transfer {
+            from: ctx.accounts.owner_token_account.to_account_info(),
+            to: ctx.accounts.vault.to_account_info(),
+            authority: ctx.accounts.owner.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        token::transfer(CpiContext::new(cpi_program, cpi_accounts), amount)?;
+
+        let now = Clock::get()?.unix_timestamp;
+        let stake_account = &mut ctx.accounts.stake_account;
+        if stake_account.version == 0 {
+            stake_account.owner = ctx.accounts.owner.key();
+            stake_account.bump = ctx.bumps.stake_account;
+            stake_account.reserved = [0; ACCOUNT_RESERVED_BYTES - 16];
         }
+        stake_account.add_stake(amount, now)?;
+        // Staking writes `recent`, so a version 1 account moves to the current layout
+        stake_account.version = StakeAccount::VERSION;
+
+        let pool = &mut ctx.accounts.staking_pool;
+        pool.total_staked = pool
+            .total_staked
+            .checked_add(amount)
+            .ok_or(AurumError::ArithmeticOverflow)?;
 
-        // Emit events
-        emit!(FeeCollected {
-            payer: ctx.accounts.payer.key(),
-            amount: final_fee,
-            fee_type: "create".to_string(),
-            timestamp: clock.unix_timestamp,
+        emit!(TokensStaked {
+            owner: stake_account.owner,
+            amount,
+            staked: stake_account.amount,
+            timestamp: now,
+        });
+
+        msg!("Staked {} tokens, {} total", amount, stake_account.amount);
+        Ok(())
+    }
+
+    /// Start the cooldown for `amount` staked tokens. They stop counting
+    /// toward a discount tier at once and can be withdrawn with
+    /// `withdraw_stake` after the pool's cooldown; unstaking again restarts
+    /// the cooldown for everything still unstaking.
+    pub fn unstake(ctx: Context<Unstake>, amount: u64) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let stake_account = &mut ctx.accounts.stake_account;
+        stake_account.remove_stake(amount)?;
+        stake_account.unstaking = stake_account
+            .unstaking
+            .checked_add(amount)
+            .ok_or(AurumError::ArithmeticOverflow)?;
+        stake_account.unstake_available_at = now
+            .checked_add(ctx.accounts.staking_pool.cooldown)
+            .ok_or(AurumError::ArithmeticOverflow)?;
+
+        let pool = &mut ctx.accounts.staking_pool;
+        pool.total_staked = pool
+            .total_staked
+            .checked_sub(amount)
+            .ok_or(AurumError::ArithmeticOverflow)?;
+        pool.total_unstaking = pool
+            .total_unstaking
+            .checked_add(amount)
+            .ok_or(AurumError::ArithmeticOverflow)?;
+
+        emit!(UnstakeRequested {
+            owner: stake_account.owner,
+            amount,
+            staked: stake_account.amount,
+            available_at: stake_account.unstake_available_at,
+            timestamp: now,
+        });
+
+        msg!(
+            "Unstaked {} tokens, withdrawable at {}",
+            amount,
+            stake_account.unstake_available_at
+        );
+        Ok(())
+    }
+
+    /// Withdraw unstaked tokens once their cooldown has passed
+    pub fn withdraw_stake(ctx: Context<WithdrawStake>) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let amount = ctx.accounts.stake_account.unstaking;
+        require!(amount > 0, AurumError::InvalidStakeAmount);
+        require!(
+            now >= ctx.accounts.stake_account.unstake_available_at,
+            AurumError::StakeCoolingDown
+        );
+
+        let seeds = &[STAKING_POOL_SEED, &[ctx.accounts.staking_pool.bump]];
+        let signer = &[&seeds[..]];
+        let cpi_accounts = token::Transfer {
+            from: ctx.accounts.vault.to_account_info(),
+            to: ctx.accounts.owner_token_account.to_account_info(),
+            authority: ctx.accounts.staking_pool.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
+        token::transfer(cpi_ctx, amount)?;
+
+        ctx.accounts.stake_account.unstaking = 0;
+        let pool = &mut ctx.accounts.staking_pool;
+        pool.total_unstaking = pool
+            .total_unstaking
+            .checked_sub(amount)
+            .ok_or(AurumError::ArithmeticOverflow)?;
+
+        emit!(StakeWithdrawn {
+            owner: ctx.accounts.owner.key(),
+            amount,
+            timestamp: now,
         });
 
+        msg!("Withdrew {} unstaked tokens", amount);
+        Ok(())
+    }
+
+    /// Create a token mint with hybrid pricing
+    pub fn create_token(
+        ctx: Context<CreateToken>,
+        decimals: u8,
+        referral_code: Option<String>,
+    ) -> Result<()> {
+        let clock = Clock::get()?;
+        let accounts = &mut *ctx.accounts;
+        let fee_paid = charge_fee(
+            ctx.program_id,
+            FeeAccounts::new(
+                &accounts.payer,
+                &accounts.config,
+                &mut accounts.treasury,
+                &mut accounts.fee_accounts,
+                &accounts.system_program,
+            ),
+            OperationKind::Create,
+            accounts.config.base_fee(OperationKind::Create, 0)?,
+            referral_code,
+        )?
+        .fee_paid();
+
         emit!(TokenCreated {
             mint: ctx.accounts.mint.key(),
             creator: ctx.accounts.payer.key(),
-            fee_paid: final_fee,
+            fee_paid,
             timestamp: clock.unix_timestamp,
         });
 
         msg!("Token created with mint: {}", ctx.accounts.mint.key());
-        msg!("Fee collected: {} lamports", final_fee);
+        msg!("Fee collected: {} lamports", fee_paid);
         Ok(())
     }
 
@@ -221,89 +902,26 @@ pub mod aurum_hybrid_pricing {
         unlock_time: i64,
         referral_code: Option<String>,
     ) -> Result<()> {
-        let config = &ctx.accounts.config;
         let clock = Clock::get()?;
 
-        // If a referral account is provided, validate PDA and referrer linkage
-        if let Some(ref referral) = ctx.accounts.referral_account {
-            let (expected_pda, _bump) = Pubkey::find_program_address(
-                &[REFERRAL_SEED, referral.referrer.as_ref()],
-                ctx.program_id,
-            );
-            require_keys_eq!(referral.key(), expected_pda, AurumError::InvalidReferralCode);
-
-            let referrer_ai = ctx
-                .accounts
-                .referrer
-                .as_ref()
-                .ok_or(AurumError::InvalidReferralCode)?;
-            require_keys_eq!(referrer_ai.key(), referral.referrer, AurumError::InvalidReferralCode);
-        }
-
         // Validate unlock time is in the future
         require!(unlock_time > clock.unix_timestamp, AurumError::InvalidUnlockTime);
 
-        // Calculate fee with referral discount
-        let base_fee = config.lock_fee;
-        let mut final_fee = base_fee;
-        let mut referrer_option: Option<Pubkey> = None;
-
-        if let Some(code) = referral_code {
-            if let Some(referral) = &ctx.accounts.referral_account {
-                if referral.code == code {
-                    final_fee = base_fee
-                        .checked_sub(config.referral_discount)
-                        .ok_or(AurumError::ArithmeticOverflow)?;
-                    referrer_option = Some(referral.referrer);
-                }
-            }
-        }
-
-        // Transfer fee to treasury
-        let cpi_context = CpiContext::new(
-            ctx.accounts.system_program.to_account_info(),
-            anchor_lang::system_program::Transfer {
-                from: ctx.accounts.locker.to_account_info(),
-                to: ctx.accounts.treasury.to_account_info(),
-            },
-        );
-        anchor_lang::system_program::transfer(cpi_context, final_fee)?;
-
-        // Update treasury
-        let treasury = &mut ctx.accounts.treasury;
-        treasury.total_collected = treasury
-            .total_collected
-            .checked_add(final_fee)
-            .ok_or(AurumError::ArithmeticOverflow)?;
-
-        // Handle referral rebate
-        if let (Some(referrer_pubkey), Some(referral)) = (referrer_option, &mut ctx.accounts.referral_account) {
-            let rebate = config.referral_rebate;
-            
-            let seeds = &[TREASURY_SEED, &[treasury.bump]];
-            let signer = &[&seeds[..]];
-            
-            let cpi_context = CpiContext::new_with_signer(
-                ctx.accounts.system_program.to_account_info(),
-                anchor_lang::system_program::Transfer {
-                    from: ctx.accounts.treasury.to_account_info(),
-                    to: ctx.accounts.referrer.as_ref().unwrap().to_account_info(),
-                },
-                signer,
-            );
-            anchor_lang::system_program::transfer(cpi_context, rebate)?;
-
-            referral.total_referrals = referral.total_referrals.checked_add(1).ok_or(AurumError::ArithmeticOverflow)?;
-            referral.rebate_earned = referral.rebate_earned.checked_add(rebate).ok_or(AurumError::ArithmeticOverflow)?;
-
-            emit!(ReferralUsed {
-                referrer: referrer_pubkey,
-                referee: ctx.accounts.locker.key(),
-                discount_amount: config.referral_discount,
-                rebate_amount: rebate,
-                timestamp: clock.unix_timestamp,
-            });
-        }
+        let accounts = &mut *ctx.accounts;
+        let fee_paid = charge_fee(
+            ctx.program_id,
+            FeeAccounts::new(
+                &accounts.locker,
+                &accounts.config,
+                &mut accounts.treasury,
+                &mut accounts.fee_accounts,
+                &accounts.system_program,
+            ),
+            OperationKind::Lock,
+            accounts.config.base_fee(OperationKind::Lock, 0)?,
+            referral_code,
+        )?
+        .fee_paid();
 
         // Transfer tokens from user to escrow
         let cpi_accounts = token::Transfer {
@@ -315,20 +933,12 @@ pub mod aurum_hybrid_pricing {
         let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
         token::transfer(cpi_ctx, amount)?;
 
-        // Emit events
-        emit!(FeeCollected {
-            payer: ctx.accounts.locker.key(),
-            amount: final_fee,
-            fee_type: "lock".to_string(),
-            timestamp: clock.unix_timestamp,
-        });
-
         emit!(TokensLocked {
             mint: ctx.accounts.mint.key(),
             locker: ctx.accounts.locker.key(),
             amount,
             unlock_time,
-            fee_paid: final_fee,
+            fee_paid,
             timestamp: clock.unix_timestamp,
         });
 
@@ -336,93 +946,557 @@ pub mod aurum_hybrid_pricing {
         Ok(())
     }
 
-    /// Burn tokens permanently
-    pub fn burn_tokens(
-        ctx: Context<BurnTokens>,
+    /// Lock AMM LP tokens, recording the pool and pair mints on a lock record
+    pub fn lock_lp_tokens(
+        ctx: Context<LockLpTokens>,
+        lock_id: u64,
         amount: u64,
+        unlock_time: i64,
         referral_code: Option<String>,
     ) -> Result<()> {
-        let config = &ctx.accounts.config;
         let clock = Clock::get()?;
 
-        // If a referral account is provided, validate PDA and referrer linkage
-        if let Some(ref referral) = ctx.accounts.referral_account {
-            let (expected_pda, _bump) = Pubkey::find_program_address(
-                &[REFERRAL_SEED, referral.referrer.as_ref()],
+        // Validate unlock time is in the future
+        require!(unlock_time > clock.unix_timestamp, AurumError::InvalidUnlockTime);
+
+        let pool_info = lp::validate_pool(&ctx.accounts.pool, &ctx.accounts.mint.key())?;
+
+        let accounts = &mut *ctx.accounts;
+        let fee_paid = charge_fee(
+            ctx.program_id,
+            FeeAccounts::new(
+                &accounts.locker,
+                &accounts.config,
+                &mut accounts.treasury,
+                &mut accounts.fee_accounts,
+                &accounts.system_program,
+            ),
+            OperationKind::Lock,
+            accounts.config.base_fee(OperationKind::Lock, 0)?,
+            referral_code,
+        )?
+        .fee_paid();
+
+        // Transfer tokens from user to escrow
+        let cpi_accounts = token::Transfer {
+            from: ctx.accounts.user_token_account.to_account_info(),
+            to: ctx.accounts.escrow_token_account.to_account_info(),
+            authority: ctx.accounts.locker.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
+        token::transfer(cpi_ctx, amount)?;
+
+        let record = &mut ctx.accounts.lock_record;
+        record.version = LockRecord::VERSION;
+        record.lock_id = lock_id;
+        record.locker = ctx.accounts.locker.key();
+        record.beneficiary = ctx.accounts.locker.key();
+        record.mint = ctx.accounts.mint.key();
+        record.amount = amount;
+        record.created_at = clock.unix_timestamp;
+        record.unlock_time = unlock_time;
+        record.pool = Some(pool_info);
+        record.bump = ctx.bumps.lock_record;
+        record.vesting_end = 0;
+        record.released = 0;
+        record.reserved = [0; ACCOUNT_RESERVED_BYTES - 16];
+
+        emit!(TokensLocked {
+            mint: record.mint,
+            locker: record.locker,
+            amount,
+            unlock_time,
+            fee_paid,
+            timestamp: clock.unix_timestamp,
+        });
+
+        emit!(LiquidityLocked {
+            lock_record: record.key(),
+            amm_program: pool_info.amm_program,
+            pool: pool_info.pool,
+            lp_mint: record.mint,
+            mint_a: pool_info.mint_a,
+            mint_b: pool_info.mint_b,
+            locker: record.locker,
+            amount,
+            unlock_time,
+            timestamp: clock.unix_timestamp,
+        });
+
+        msg!("LP tokens locked: {} of pool {} until {}", amount, pool_info.pool, unlock_time);
+        Ok(())
+    }
+
+    /// Lock tokens for many beneficiaries in one instruction, charging a single
+    /// `lock_fee`. Lock record PDAs `["lock", mint, locker, base_lock_id + i]`
+    /// are passed as writable remaining accounts in entry order.
+    pub fn batch_lock<'info>(
+        ctx: Context<'_, '_, 'info, 'info, BatchLock<'info>>,
+        base_lock_id: u64,
+        entries: Vec<BatchLockEntry>,
+        referral_code: Option<String>,
+    ) -> Result<()> {
+        let clock = Clock::get()?;
+        require!(
+            !entries.is_empty()
+                && entries.len() <= MAX_BATCH_LOCK_ENTRIES
+                && ctx.remaining_accounts.len() == entries.len(),
+            AurumError::InvalidBatch
+        );
+
+        let mut total_amount: u64 = 0;
+        for entry in &entries {
+            require!(entry.amount > 0, AurumError::InvalidBatch);
+            require!(entry.unlock_time > clock.unix_timestamp, AurumError::InvalidUnlockTime);
+            if let Some(vesting_end) = entry.vesting_end {
+                require!(vesting_end > entry.unlock_time, AurumError::InvalidVestingSchedule);
+            }
+            total_amount = total_amount
+                .checked_add(entry.amount)
+                .ok_or(AurumError::ArithmeticOverflow)?;
+        }
+
+        let accounts = &mut *ctx.accounts;
+        let fee_paid = charge_fee(
+            ctx.program_id,
+            FeeAccounts::new(
+                &accounts.locker,
+                &accounts.config,
+                &mut accounts.treasury,
+                &mut accounts.fee_accounts,
+                &accounts.system_program,
+            ),
+            OperationKind::Lock,
+            accounts.config.base_fee(OperationKind::Lock, 0)?,
+            referral_code,
+        )?
+        .fee_paid();
+
+        // Transfer the batch total from user to escrow in one CPI
+        let cpi_accounts = token::Transfer {
+            from: ctx.accounts.user_token_account.to_account_info(),
+            to: ctx.accounts.escrow_token_account.to_account_info(),
+            authority: ctx.accounts.locker.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
+        token::transfer(cpi_ctx, total_amount)?;
+
+        let mint = ctx.accounts.mint.key();
+        let locker = ctx.accounts.locker.key();
+
+        // The fee is charged once per batch; each entry's event carries an
+        // equal share, with the remainder on the first entry
+        let fee_share = fee_paid / entries.len() as u64;
+        let fee_remainder = fee_paid % entries.len() as u64;
+
+        for (i, (entry, record_info)) in entries.iter().zip(ctx.remaining_accounts.iter()).enumerate() {
+            let lock_id = base_lock_id
+                .checked_add(i as u64)
+                .ok_or(AurumError::ArithmeticOverflow)?;
+            let lock_id_bytes = lock_id.to_le_bytes();
+            let (expected, bump) = Pubkey::find_program_address(
+                &[LOCK_SEED, mint.as_ref(), locker.as_ref(), &lock_id_bytes],
                 ctx.program_id,
             );
-            require_keys_eq!(referral.key(), expected_pda, AurumError::InvalidReferralCode);
-
-            let referrer_ai = ctx
-                .accounts
-                .referrer
-                .as_ref()
-                .ok_or(AurumError::InvalidReferralCode)?;
-            require_keys_eq!(referrer_ai.key(), referral.referrer, AurumError::InvalidReferralCode);
-        }
-
-        // Calculate fee with referral discount
-        let base_fee = config.burn_fee;
-        let mut final_fee = base_fee;
-        let mut referrer_option: Option<Pubkey> = None;
-
-        if let Some(code) = referral_code {
-            if let Some(referral) = &ctx.accounts.referral_account {
-                if referral.code == code {
-                    final_fee = base_fee
-                        .checked_sub(config.referral_discount)
-                        .ok_or(AurumError::ArithmeticOverflow)?;
-                    referrer_option = Some(referral.referrer);
-                }
-            }
+            require_keys_eq!(record_info.key(), expected, AurumError::InvalidBatch);
+
+            let bump_bytes = [bump];
+            init_pda_account(
+                record_info,
+                &ctx.accounts.locker.to_account_info(),
+                &ctx.accounts.system_program.to_account_info(),
+                LockRecord::SPACE,
+                ctx.program_id,
+                &[LOCK_SEED, mint.as_ref(), locker.as_ref(), &lock_id_bytes, &bump_bytes],
+            )?;
+
+            let record = LockRecord {
+                version: LockRecord::VERSION,
+                lock_id,
+                locker,
+                beneficiary: entry.beneficiary,
+                mint,
+                amount: entry.amount,
+                created_at: clock.unix_timestamp,
+                unlock_time: entry.unlock_time,
+                pool: None,
+                bump,
+                vesting_end: entry.vesting_end.unwrap_or(0),
+                released: 0,
+                reserved: [0; ACCOUNT_RESERVED_BYTES - 16],
+            };
+            record.try_serialize(&mut &mut record_info.try_borrow_mut_data()?[..])?;
+
+            emit!(TokensLocked {
+                mint,
+                locker,
+                amount: entry.amount,
+                unlock_time: entry.unlock_time,
+                fee_paid: if i == 0 { fee_share + fee_remainder } else { fee_share },
+                timestamp: clock.unix_timestamp,
+            });
         }
 
-        // Transfer fee to treasury
-        let cpi_context = CpiContext::new(
-            ctx.accounts.system_program.to_account_info(),
-            anchor_lang::system_program::Transfer {
-                from: ctx.accounts.burner.to_account_info(),
-                to: ctx.accounts.treasury.to_account_info(),
-            },
-        );
-        anchor_lang::system_program::transfer(cpi_context, final_fee)?;
+        emit!(BatchLocked {
+            mint,
+            locker,
+            entries: entries.len() as u32,
+            total_amount,
+            fee_paid,
+            timestamp: clock.unix_timestamp,
+        });
 
-        // Update treasury
-        let treasury = &mut ctx.accounts.treasury;
-        treasury.total_collected = treasury
-            .total_collected
-            .checked_add(final_fee)
+        msg!("Batch locked: {} entries, {} tokens", entries.len(), total_amount);
+        Ok(())
+    }
+
+    /// Release the vested, unreleased tokens of a lock record to its
+    /// beneficiary. The record is closed back to the locker once fully released.
+    pub fn unlock_tokens(ctx: Context<UnlockTokens>) -> Result<()> {
+        let clock = Clock::get()?;
+        let releasable = ctx.accounts.lock_record.releasable(clock.unix_timestamp);
+        require!(releasable > 0, AurumError::StillLocked);
+
+        let seeds = &[TREASURY_SEED, &[ctx.accounts.treasury.bump]];
+        let signer = &[&seeds[..]];
+        let cpi_accounts = token::Transfer {
+            from: ctx.accounts.escrow_token_account.to_account_info(),
+            to: ctx.accounts.beneficiary_token_account.to_account_info(),
+            authority: ctx.accounts.treasury.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
+        token::transfer(cpi_ctx, releasable)?;
+
+        let record = &mut ctx.accounts.lock_record;
+        record.released = record
+            .released
+            .checked_add(releasable)
             .ok_or(AurumError::ArithmeticOverflow)?;
 
-        // Handle referral rebate
-        if let (Some(referrer_pubkey), Some(referral)) = (referrer_option, &mut ctx.accounts.referral_account) {
-            let rebate = config.referral_rebate;
-            
-            let seeds = &[TREASURY_SEED, &[treasury.bump]];
-            let signer = &[&seeds[..]];
-            
-            let cpi_context = CpiContext::new_with_signer(
-                ctx.accounts.system_program.to_account_info(),
-                anchor_lang::system_program::Transfer {
-                    from: ctx.accounts.treasury.to_account_info(),
-                    to: ctx.accounts.referrer.as_ref().unwrap().to_account_info(),
-                },
-                signer,
+        emit!(TokensUnlocked {
+            lock_record: record.key(),
+            mint: record.mint,
+            beneficiary: record.beneficiary,
+            amount: releasable,
+            timestamp: clock.unix_timestamp,
+        });
+
+        msg!("Tokens unlocked: {} to {}", releasable, record.beneficiary);
+
+        if record.released >= record.amount {
+            record.close(ctx.accounts.locker.to_account_info())?;
+        }
+        Ok(())
+    }
+
+    /// Send one mint to many recipients. Remaining accounts are
+    /// `(recipient wallet, recipient ATA)` pairs in `amounts` order; missing
+    /// ATAs are created (paid by the sender) when `create_missing_atas` is set.
+    pub fn multisend<'info>(
+        ctx: Context<'_, '_, 'info, 'info, Multisend<'info>>,
+        amounts: Vec<u64>,
+        create_missing_atas: bool,
+        referral_code: Option<String>,
+    ) -> Result<()> {
+        let clock = Clock::get()?;
+        let recipients = amounts.len();
+        require!(
+            recipients > 0
+                && recipients <= MAX_MULTISEND_RECIPIENTS
+                && ctx.remaining_accounts.len() == recipients * 2,
+            AurumError::InvalidBatch
+        );
+
+        let base_fee = ctx
+            .accounts
+            .config
+            .base_fee(OperationKind::Multisend, recipients as u64)?;
+
+        let accounts = &mut *ctx.accounts;
+        let fee_paid = charge_fee(
+            ctx.program_id,
+            FeeAccounts::new(
+                &accounts.sender,
+                &accounts.config,
+                &mut accounts.treasury,
+                &mut accounts.fee_accounts,
+                &accounts.system_program,
+            ),
+            OperationKind::Multisend,
+            base_fee,
+            referral_code,
+        )?
+        .fee_paid();
+
+        let mint = ctx.accounts.mint.key();
+        let mut total_amount: u64 = 0;
+        let mut atas_created: u32 = 0;
+
+        for (amount, pair) in amounts.iter().zip(ctx.remaining_accounts.chunks(2)) {
+            let (wallet, ata) = (&pair[0], &pair[1]);
+            require_keys_eq!(
+                ata.key(),
+                get_associated_token_address(wallet.key, &mint),
+                AurumError::InvalidBatch
             );
-            anchor_lang::system_program::transfer(cpi_context, rebate)?;
 
-            referral.total_referrals = referral.total_referrals.checked_add(1).ok_or(AurumError::ArithmeticOverflow)?;
-            referral.rebate_earned = referral.rebate_earned.checked_add(rebate).ok_or(AurumError::ArithmeticOverflow)?;
+            if ata.data_is_empty() {
+                require!(create_missing_atas, AurumError::MissingTokenAccount);
+                associated_token::create(CpiContext::new(
+                    ctx.accounts.associated_token_program.to_account_info(),
+                    associated_token::Create {
+                        payer: ctx.accounts.sender.to_account_info(),
+                        associated_token: ata.clone(),
+                        authority: wallet.clone(),
+                        mint: ctx.accounts.mint.to_account_info(),
+                        system_program: ctx.accounts.system_program.to_account_info(),
+                        token_program: ctx.accounts.token_program.to_account_info(),
+                    },
+                ))?;
+                atas_created += 1;
+            }
 
-            emit!(ReferralUsed {
-                referrer: referrer_pubkey,
-                referee: ctx.accounts.burner.key(),
-                discount_amount: config.referral_discount,
-                rebate_amount: rebate,
-                timestamp: clock.unix_timestamp,
-            });
+            let cpi_accounts = token::Transfer {
+                from: ctx.accounts.sender_token_account.to_account_info(),
+                to: ata.clone(),
+                authority: ctx.accounts.sender.to_account_info(),
+            };
+            let cpi_program = ctx.accounts.token_program.to_account_info();
+            token::transfer(CpiContext::new(cpi_program, cpi_accounts), *amount)?;
+
+            total_amount = total_amount
+                .checked_add(*amount)
+                .ok_or(AurumError::ArithmeticOverflow)?;
         }
 
+        emit!(MultisendCompleted {
+            mint,
+            sender: ctx.accounts.sender.key(),
+            recipients: recipients as u32,
+            total_amount,
+            atas_created,
+            fee_paid,
+            timestamp: clock.unix_timestamp,
+        });
+
+        msg!("Multisend: {} tokens to {} recipients", total_amount, recipients);
+        Ok(())
+    }
+
+    /// Fund a Merkle distributor for airdrops too large for `multisend`.
+    /// Charged like a multisend to `max_num_nodes` recipients.
+    pub fn create_distributor(
+        ctx: Context<CreateDistributor>,
+        distributor_id: u64,
+        merkle_root: [u8; 32],
+        total_amount: u64,
+        max_num_nodes: u64,
+        clawback_time: i64,
+        referral_code: Option<String>,
+    ) -> Result<()> {
+        let clock = Clock::get()?;
+        require!(
+            total_amount > 0 && max_num_nodes > 0 && clawback_time > clock.unix_timestamp,
+            AurumError::InvalidDistributor
+        );
+
+        let base_fee = ctx
+            .accounts
+            .config
+            .base_fee(OperationKind::Multisend, max_num_nodes)?;
+
+        let accounts = &mut *ctx.accounts;
+        let fee_paid = charge_fee(
+            ctx.program_id,
+            FeeAccounts::new(
+                &accounts.authority,
+                &accounts.config,
+                &mut accounts.treasury,
+                &mut accounts.fee_accounts,
+                &accounts.system_program,
+            ),
+            OperationKind::Multisend,
+            base_fee,
+            referral_code,
+        )?
+        .fee_paid();
+
+        let cpi_accounts = token::Transfer {
+            from: ctx.accounts.authority_token_account.to_account_info(),
+            to: ctx.accounts.vault.to_account_info(),
+            authority: ctx.accounts.authority.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        token::transfer(CpiContext::new(cpi_program, cpi_accounts), total_amount)?;
+
+        let distributor = &mut ctx.accounts.distributor;
+        distributor.version = Distributor::VERSION;
+        distributor.authority = ctx.accounts.authority.key();
+        distributor.distributor_id = distributor_id;
+        distributor.mint = ctx.accounts.mint.key();
+        distributor.vault = ctx.accounts.vault.key();
+        distributor.merkle_root = merkle_root;
+        distributor.total_amount = total_amount;
+        distributor.max_num_nodes = max_num_nodes;
+        distributor.total_claimed = 0;
+        distributor.num_claims = 0;
+        distributor.clawback_time = clawback_time;
+        distributor.clawed_back = false;
+        distributor.bump = ctx.bumps.distributor;
+
+        emit!(DistributorCreated {
+            distributor: distributor.key(),
+            authority: distributor.authority,
+            mint: distributor.mint,
+            merkle_root,
+            total_amount,
+            max_num_nodes,
+            clawback_time,
+            fee_paid,
+            timestamp: clock.unix_timestamp,
+        });
+
+        msg!("Distributor created: {} tokens for {} recipients", total_amount, max_num_nodes);
+        Ok(())
+    }
+
+    /// Claim an airdrop allocation with a Merkle proof (see `merkle::leaf`)
+    pub fn claim(
+        ctx: Context<Claim>,
+        index: u64,
+        amount: u64,
+        proof: Vec<[u8; 32]>,
+    ) -> Result<()> {
+        let clock = Clock::get()?;
+        let distributor = &ctx.accounts.distributor;
+        require!(!distributor.clawed_back, AurumError::DistributorClosed);
+        require!(proof.len() <= MAX_MERKLE_PROOF_LEN, AurumError::InvalidProof);
+
+        let claimant = ctx.accounts.claimant.key();
+        require!(
+            merkle::verify(&proof, &distributor.merkle_root, merkle::leaf(index, &claimant, amount)),
+            AurumError::InvalidProof
+        );
+
+        let total_claimed = distributor
+            .total_claimed
+            .checked_add(amount)
+            .ok_or(AurumError::ArithmeticOverflow)?;
+        require!(total_claimed <= distributor.total_amount, AurumError::InvalidDistributor);
+        require!(distributor.num_claims < distributor.max_num_nodes, AurumError::InvalidDistributor);
+
+        let id_bytes = distributor.distributor_id.to_le_bytes();
+        let seeds = &[
+            DISTRIBUTOR_SEED,
+            distributor.authority.as_ref(),
+            id_bytes.as_ref(),
+            &[distributor.bump],
+        ];
+        let signer = &[&seeds[..]];
+        let cpi_accounts = token::Transfer {
+            from: ctx.accounts.vault.to_account_info(),
+            to: ctx.accounts.claimant_token_account.to_account_info(),
+            authority: ctx.accounts.distributor.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
+        token::transfer(cpi_ctx, amount)?;
+
+        let distributor = &mut ctx.accounts.distributor;
+        distributor.total_claimed = total_claimed;
+        distributor.num_claims += 1;
+
+        let claim_status = &mut ctx.accounts.claim_status;
+        claim_status.version = ClaimStatus::VERSION;
+        claim_status.distributor = distributor.key();
+        claim_status.index = index;
+        claim_status.claimant = claimant;
+        claim_status.amount = amount;
+        claim_status.claimed_at = clock.unix_timestamp;
+        claim_status.bump = ctx.bumps.claim_status;
+
+        emit!(TokensClaimed {
+            distributor: distributor.key(),
+            claimant,
+            index,
+            amount,
+            timestamp: clock.unix_timestamp,
+        });
+
+        msg!("Claimed {} tokens (index {})", amount, index);
+        Ok(())
+    }
+
+    /// Return unclaimed tokens to the distributor authority after the
+    /// clawback time; closes the distributor to further claims
+    pub fn clawback(ctx: Context<Clawback>) -> Result<()> {
+        let clock = Clock::get()?;
+        let distributor = &ctx.accounts.distributor;
+        require!(!distributor.clawed_back, AurumError::DistributorClosed);
+        require!(
+            clock.unix_timestamp >= distributor.clawback_time,
+            AurumError::ClawbackTooEarly
+        );
+
+        let amount = ctx.accounts.vault.amount;
+        let id_bytes = distributor.distributor_id.to_le_bytes();
+        let seeds = &[
+            DISTRIBUTOR_SEED,
+            distributor.authority.as_ref(),
+            id_bytes.as_ref(),
+            &[distributor.bump],
+        ];
+        let signer = &[&seeds[..]];
+        let cpi_accounts = token::Transfer {
+            from: ctx.accounts.vault.to_account_info(),
+            to: ctx.accounts.authority_token_account.to_account_info(),
+            authority: ctx.accounts.distributor.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
+        token::transfer(cpi_ctx, amount)?;
+
+        let distributor = &mut ctx.accounts.distributor;
+        distributor.clawed_back = true;
+
+        emit!(DistributorClawedBack {
+            distributor: distributor.key(),
+            authority: distributor.authority,
+            amount,
+            timestamp: clock.unix_timestamp,
+        });
+
+        msg!("Clawed back {} unclaimed tokens", amount);
+        Ok(())
+    }
+
+    /// Burn tokens permanently
+    pub fn burn_tokens(
+        ctx: Context<BurnTokens>,
+        amount: u64,
+        referral_code: Option<String>,
+    ) -> Result<()> {
+        let clock = Clock::get()?;
+
+        let accounts = &mut *ctx.accounts;
+        let fee_paid = charge_fee(
+            ctx.program_id,
+            FeeAccounts::new(
+                &accounts.burner,
+                &accounts.config,
+                &mut accounts.treasury,
+                &mut accounts.fee_accounts,
+                &accounts.system_program,
+            ),
+            OperationKind::Burn,
+            accounts.config.base_fee(OperationKind::Burn, 0)?,
+            referral_code,
+        )?
+        .fee_paid();
+
         // Burn tokens
         let cpi_accounts = Burn {
             mint: ctx.accounts.mint.to_account_info(),
@@ -433,19 +1507,48 @@ pub mod aurum_hybrid_pricing {
         let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
         token::burn(cpi_ctx, amount)?;
 
-        // Emit events
-        emit!(FeeCollected {
-            payer: ctx.accounts.burner.key(),
-            amount: final_fee,
-            fee_type: "burn".to_string(),
-            timestamp: clock.unix_timestamp,
-        });
+        // Update per-mint burn statistics
+        let burner = ctx.accounts.burner.key();
+        let stats = &mut ctx.accounts.burn_stats;
+        if stats.version == 0 {
+            stats.version = BurnStats::VERSION;
+            stats.mint = ctx.accounts.mint.key();
+            stats.bump = ctx.bumps.burn_stats;
+        }
+        let sequence = stats.burn_count;
+        stats.record(burner, amount, clock.unix_timestamp)?;
+
+        // Issue a receipt when the caller supplied one
+        if let Some(receipt) = &mut ctx.accounts.burn_receipt {
+            ctx.accounts.mint.reload()?;
+            receipt.version = BurnReceipt::VERSION;
+            receipt.mint = ctx.accounts.mint.key();
+            receipt.burner = burner;
+            receipt.amount = amount;
+            receipt.sequence = sequence;
+            receipt.supply_after = ctx.accounts.mint.supply;
+            receipt.total_burned_after = stats.total_burned;
+            receipt.burned_at = clock.unix_timestamp;
+            receipt.bump = ctx.bumps.burn_receipt.ok_or(AurumError::InvalidAccountLayout)?;
+        }
 
         emit!(TokensBurned {
             mint: ctx.accounts.mint.key(),
             burner: ctx.accounts.burner.key(),
             amount,
-            fee_paid: final_fee,
+            fee_paid,
+            timestamp: clock.unix_timestamp,
+        });
+
+        let stats = &ctx.accounts.burn_stats;
+        emit!(BurnRecorded {
+            mint: stats.mint,
+            burner: stats.last_burner,
+            amount,
+            sequence,
+            total_burned: stats.total_burned,
+            burn_count: stats.burn_count,
+            receipt: ctx.accounts.burn_receipt.as_ref().map(|receipt| receipt.key()),
             timestamp: clock.unix_timestamp,
         });
 
@@ -453,202 +1556,2000 @@ pub mod aurum_hybrid_pricing {
         Ok(())
     }
 
-    /// Withdraw accumulated fees (owner only)
-    pub fn withdraw_fees(ctx: Context<WithdrawFees>, amount: u64) -> Result<()> {
-        let clock = Clock::get()?;
-        let treasury = &ctx.accounts.treasury;
+    /// Burn the whole balance of the caller's ATA and close it, returning the
+    /// rent deposit to the caller. Charged and recorded like `burn_tokens`;
+    /// an empty account is closed for free, like `close_dust_accounts`, since
+    /// nothing is burned.
+    pub fn burn_all_and_close(
+        ctx: Context<BurnAllAndClose>,
+        referral_code: Option<String>,
+    ) -> Result<()> {
+        let clock = Clock::get()?;
+        let amount = ctx.accounts.user_token_account.amount;
+        let mut fee_paid = 0;
+        if amount > 0 {
+            let accounts = &mut *ctx.accounts;
+            fee_paid = charge_fee(
+                ctx.program_id,
+                FeeAccounts::new(
+                    &accounts.burner,
+                    &accounts.config,
+                    &mut accounts.treasury,
+                    &mut accounts.fee_accounts,
+                    &accounts.system_program,
+                ),
+                OperationKind::Burn,
+                accounts.config.base_fee(OperationKind::Burn, 0)?,
+                referral_code,
+            )?
+            .fee_paid();
+
+            let cpi_accounts = Burn {
+                mint: ctx.accounts.mint.to_account_info(),
+                from: ctx.accounts.user_token_account.to_account_info(),
+                authority: ctx.accounts.burner.to_account_info(),
+            };
+            let cpi_program = ctx.accounts.token_program.to_account_info();
+            token::burn(CpiContext::new(cpi_program, cpi_accounts), amount)?;
+        }
+
+        let burner = ctx.accounts.burner.key();
+        let stats = &mut ctx.accounts.burn_stats;
+        if stats.version == 0 {
+            stats.version = BurnStats::VERSION;
+            stats.mint = ctx.accounts.mint.key();
+            stats.bump = ctx.bumps.burn_stats;
+        }
+        // Closing an empty account is not a burn
+        if amount > 0 {
+            stats.record(burner, amount, clock.unix_timestamp)?;
+        }
+
+        let rent_reclaimed = ctx.accounts.user_token_account.to_account_info().lamports();
+        let cpi_accounts = token::CloseAccount {
+            account: ctx.accounts.user_token_account.to_account_info(),
+            destination: ctx.accounts.burner.to_account_info(),
+            authority: ctx.accounts.burner.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        token::close_account(CpiContext::new(cpi_program, cpi_accounts))?;
+
+        if amount > 0 {
+            emit!(TokensBurned {
+                mint: ctx.accounts.mint.key(),
+                burner,
+                amount,
+                fee_paid,
+                timestamp: clock.unix_timestamp,
+            });
+        }
+
+        emit!(TokenAccountClosed {
+            mint: ctx.accounts.mint.key(),
+            owner: burner,
+            token_account: ctx.accounts.user_token_account.key(),
+            amount_burned: amount,
+            rent_reclaimed,
+            timestamp: clock.unix_timestamp,
+        });
+
+        msg!("Burned {} tokens and closed account", amount);
+        Ok(())
+    }
+
+    /// Burn leftover dust and close up to `MAX_DUST_ACCOUNTS` of the caller's
+    /// token accounts, passed as `(token account, mint)` remaining account
+    /// pairs. Each balance must be at most `MAX_DUST_AMOUNT`; since only dust
+    /// is destroyed, no fee is charged and the burns are not added to
+    /// `BurnStats`. Rent goes back to the caller.
+    pub fn close_dust_accounts<'info>(
+        ctx: Context<'_, '_, 'info, 'info, CloseDustAccounts<'info>>,
+    ) -> Result<()> {
+        let clock = Clock::get()?;
+        let pairs = ctx.remaining_accounts.len() / 2;
+        require!(
+            pairs > 0 && pairs <= MAX_DUST_ACCOUNTS && ctx.remaining_accounts.len() == pairs * 2,
+            AurumError::InvalidBatch
+        );
+
+        let owner = ctx.accounts.owner.key();
+        let mut rent_reclaimed: u64 = 0;
+        for pair in ctx.remaining_accounts.chunks(2) {
+            let (token_account_ai, mint_ai) = (&pair[0], &pair[1]);
+            let token_account = Account::<TokenAccount>::try_from(token_account_ai)?;
+            require_keys_eq!(token_account.owner, owner, AurumError::Unauthorized);
+            require_keys_eq!(token_account.mint, mint_ai.key(), AurumError::InvalidBatch);
+
+            let amount = token_account.amount;
+            require!(amount <= MAX_DUST_AMOUNT, AurumError::BalanceNotDust);
+            if amount > 0 {
+                let cpi_accounts = Burn {
+                    mint: mint_ai.clone(),
+                    from: token_account_ai.clone(),
+                    authority: ctx.accounts.owner.to_account_info(),
+                };
+                let cpi_program = ctx.accounts.token_program.to_account_info();
+                token::burn(CpiContext::new(cpi_program, cpi_accounts), amount)?;
+            }
+
+            let rent = token_account_ai.lamports();
+            let cpi_accounts = token::CloseAccount {
+                account: token_account_ai.clone(),
+                destination: ctx.accounts.owner.to_account_info(),
+                authority: ctx.accounts.owner.to_account_info(),
+            };
+            let cpi_program = ctx.accounts.token_program.to_account_info();
+            token::close_account(CpiContext::new(cpi_program, cpi_accounts))?;
+            rent_reclaimed = rent_reclaimed
+                .checked_add(rent)
+                .ok_or(AurumError::ArithmeticOverflow)?;
+
+            emit!(TokenAccountClosed {
+                mint: token_account.mint,
+                owner,
+                token_account: token_account_ai.key(),
+                amount_burned: amount,
+                rent_reclaimed: rent,
+                timestamp: clock.unix_timestamp,
+            });
+        }
+
+        msg!("Closed {} token accounts, reclaimed {} lamports", pairs, rent_reclaimed);
+        Ok(())
+    }
+
+    /// Create a recurring burn: `amount_per_interval` becomes burnable every
+    /// `interval` seconds from `start_time`. Tokens are deposited with
+    /// `fund_burn_schedule` and can only leave the vault through `crank_burn`.
+    /// Charges `burn_fee`.
+    pub fn create_burn_schedule(
+        ctx: Context<CreateBurnSchedule>,
+        schedule_id: u64,
+        amount_per_interval: u64,
+        interval: i64,
+        start_time: i64,
+        bounty_per_crank: u64,
+        referral_code: Option<String>,
+    ) -> Result<()> {
+        let clock = Clock::get()?;
+        require!(
+            amount_per_interval > 0 && interval > 0,
+            AurumError::InvalidBurnSchedule
+        );
+
+        let accounts = &mut *ctx.accounts;
+        let fee_paid = charge_fee(
+            ctx.program_id,
+            FeeAccounts::new(
+                &accounts.authority,
+                &accounts.config,
+                &mut accounts.treasury,
+                &mut accounts.fee_accounts,
+                &accounts.system_program,
+            ),
+            OperationKind::Burn,
+            accounts.config.base_fee(OperationKind::Burn, 0)?,
+            referral_code,
+        )?
+        .fee_paid();
+
+        let schedule = &mut ctx.accounts.schedule;
+        schedule.version = BurnSchedule::VERSION;
+        schedule.authority = ctx.accounts.authority.key();
+        schedule.schedule_id = schedule_id;
+        schedule.mint = ctx.accounts.mint.key();
+        schedule.vault = ctx.accounts.vault.key();
+        schedule.amount_per_interval = amount_per_interval;
+        schedule.interval = interval;
+        schedule.next_burn_at = start_time.max(clock.unix_timestamp);
+        schedule.bounty_per_crank = bounty_per_crank;
+        schedule.total_deposited = 0;
+        schedule.total_burned = 0;
+        schedule.crank_count = 0;
+        schedule.bump = ctx.bumps.schedule;
+
+        emit!(BurnScheduleCreated {
+            schedule: schedule.key(),
+            authority: schedule.authority,
+            mint: schedule.mint,
+            amount_per_interval,
+            interval,
+            next_burn_at: schedule.next_burn_at,
+            bounty_per_crank,
+            fee_paid,
+            timestamp: clock.unix_timestamp,
+        });
+
+        msg!("Burn schedule created: {} every {}s", amount_per_interval, interval);
+        Ok(())
+    }
+
+    /// Add tokens to a burn schedule's vault and/or lamports to its crank
+    /// bounty pool. Anyone may fund a schedule.
+    pub fn fund_burn_schedule(
+        ctx: Context<FundBurnSchedule>,
+        token_amount: u64,
+        bounty_lamports: u64,
+    ) -> Result<()> {
+        let clock = Clock::get()?;
+        require!(token_amount > 0 || bounty_lamports > 0, AurumError::InvalidBurnSchedule);
+
+        if token_amount > 0 {
+            let cpi_accounts = token::Transfer {
+                from: ctx.accounts.funder_token_account.to_account_info(),
+                to: ctx.accounts.vault.to_account_info(),
+                authority: ctx.accounts.funder.to_account_info(),
+            };
+            let cpi_program = ctx.accounts.token_program.to_account_info();
+            token::transfer(CpiContext::new(cpi_program, cpi_accounts), token_amount)?;
+        }
+
+        if bounty_lamports > 0 {
+            let cpi_context = CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                anchor_lang::system_program::Transfer {
+                    from: ctx.accounts.funder.to_account_info(),
+                    to: ctx.accounts.schedule.to_account_info(),
+                },
+            );
+            anchor_lang::system_program::transfer(cpi_context, bounty_lamports)?;
+        }
+
+        let schedule = &mut ctx.accounts.schedule;
+        schedule.total_deposited = schedule
+            .total_deposited
+            .checked_add(token_amount)
+            .ok_or(AurumError::ArithmeticOverflow)?;
+
+        emit!(BurnScheduleFunded {
+            schedule: schedule.key(),
+            funder: ctx.accounts.funder.key(),
+            token_amount,
+            bounty_lamports,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Burn everything due on a schedule (permissionless). Missed intervals
+    /// are caught up in one crank; the caller receives the bounty if the
+    /// schedule holds enough lamports.
+    pub fn crank_burn(ctx: Context<CrankBurn>) -> Result<()> {
+        let clock = Clock::get()?;
+        let schedule = &ctx.accounts.schedule;
+        let due = schedule.intervals_due(clock.unix_timestamp);
+        require!(due > 0, AurumError::NothingDue);
+
+        // Only whole intervals the vault can cover are burned; the rest stay
+        // due until the schedule is funded again
+        let intervals = due.min(ctx.accounts.vault.amount / schedule.amount_per_interval);
+        require!(intervals > 0, AurumError::BurnScheduleUnderfunded);
+        let amount = schedule
+            .amount_per_interval
+            .checked_mul(intervals)
+            .ok_or(AurumError::ArithmeticOverflow)?;
+
+        let id_bytes = schedule.schedule_id.to_le_bytes();
+        let seeds = &[
+            BURN_SCHEDULE_SEED,
+            schedule.mint.as_ref(),
+            schedule.authority.as_ref(),
+            id_bytes.as_ref(),
+            &[schedule.bump],
+        ];
+        let signer = &[&seeds[..]];
+        let cpi_accounts = Burn {
+            mint: ctx.accounts.mint.to_account_info(),
+            from: ctx.accounts.vault.to_account_info(),
+            authority: ctx.accounts.schedule.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
+        token::burn(cpi_ctx, amount)?;
+
+        // The tokens are the schedule authority's; the cranker only triggers the burn
+        let cranker = ctx.accounts.cranker.key();
+        let stats = &mut ctx.accounts.burn_stats;
+        if stats.version == 0 {
+            stats.version = BurnStats::VERSION;
+            stats.mint = ctx.accounts.mint.key();
+            stats.bump = ctx.bumps.burn_stats;
+        }
+        stats.record(ctx.accounts.schedule.authority, amount, clock.unix_timestamp)?;
+
+        let bounty_paid = BurnSchedule::pay_bounty(
+            &ctx.accounts.schedule.to_account_info(),
+            &ctx.accounts.cranker.to_account_info(),
+            ctx.accounts.schedule.bounty_per_crank,
+        )?;
+
+        let schedule = &mut ctx.accounts.schedule;
+        schedule.next_burn_at = (intervals as i64)
+            .checked_mul(schedule.interval)
+            .and_then(|elapsed| schedule.next_burn_at.checked_add(elapsed))
+            .ok_or(AurumError::ArithmeticOverflow)?;
+        schedule.total_burned = schedule
+            .total_burned
+            .checked_add(amount)
+            .ok_or(AurumError::ArithmeticOverflow)?;
+        schedule.crank_count = schedule
+            .crank_count
+            .checked_add(1)
+            .ok_or(AurumError::ArithmeticOverflow)?;
+
+        emit!(ScheduledBurnExecuted {
+            schedule: schedule.key(),
+            mint: schedule.mint,
+            cranker,
+            amount,
+            intervals,
+            bounty_paid,
+            next_burn_at: schedule.next_burn_at,
+            timestamp: clock.unix_timestamp,
+        });
+
+        msg!("Scheduled burn: {} tokens ({} intervals)", amount, intervals);
+        Ok(())
+    }
+
+    /// Withdraw accumulated fees (owner, or admin multisig co-signers
+    /// passed as remaining accounts) to an allowlisted destination.
+    /// Defaults to the `owner` account when no destination is supplied.
+    pub fn withdraw_fees(ctx: Context<WithdrawFees>, amount: u64) -> Result<()> {
+        let clock = Clock::get()?;
+        ctx.accounts
+            .config
+            .require_admin(&ctx.accounts.owner.to_account_info(), ctx.remaining_accounts)?;
+
+        let destination = match &ctx.accounts.destination {
+            Some(destination) => destination.to_account_info(),
+            None => ctx.accounts.owner.to_account_info(),
+        };
+        ctx.accounts
+            .policy
+            .record_withdrawal(destination.key, amount, &clock)?;
+
+        // Transfer SOL from treasury to destination
+        Treasury::pay_out(&ctx.accounts.treasury.to_account_info(), &destination, amount)?;
+
+        let treasury = &mut ctx.accounts.treasury;
+        treasury.total_withdrawn = treasury
+            .total_withdrawn
+            .checked_add(amount)
+            .ok_or(AurumError::ArithmeticOverflow)?;
+
+        emit!(FeesWithdrawn {
+            recipient: destination.key(),
+            amount,
+            timestamp: clock.unix_timestamp,
+        });
+
+        msg!("Fees withdrawn: {} lamports to {}", amount, destination.key());
+        Ok(())
+    }
+
+    /// Set the treasury withdrawal policy (owner, or admin multisig co-signers
+    /// passed as remaining accounts)
+    pub fn set_treasury_policy(
+        ctx: Context<SetTreasuryPolicy>,
+        allowed_destinations: Vec<Pubkey>,
+        epoch_cap: u64,
+        cooldown_seconds: i64,
+    ) -> Result<()> {
+        ctx.accounts
+            .config
+            .require_admin(&ctx.accounts.owner.to_account_info(), ctx.remaining_accounts)?;
+        require!(
+            !allowed_destinations.is_empty()
+                && allowed_destinations.len() <= MAX_WITHDRAWAL_DESTINATIONS
+                && cooldown_seconds >= 0,
+            AurumError::InvalidTreasuryPolicy
+        );
+
+        let policy = &mut ctx.accounts.policy;
+        policy.version = TreasuryPolicy::VERSION;
+        policy.allowed_destinations = allowed_destinations;
+        policy.epoch_cap = epoch_cap;
+        policy.cooldown_seconds = cooldown_seconds;
+        policy.bump = ctx.bumps.policy;
+
+        emit!(TreasuryPolicyUpdated {
+            allowed_destinations: policy.allowed_destinations.clone(),
+            epoch_cap,
+            cooldown_seconds,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        msg!(
+            "Treasury policy updated: {} destinations, cap {} per epoch, cooldown {}s",
+            policy.allowed_destinations.len(),
+            epoch_cap,
+            cooldown_seconds
+        );
+        Ok(())
+    }
+
+    /// Add a withdrawal destination to the allowlist (owner, or admin multisig
+    /// co-signers passed as remaining accounts)
+    pub fn add_withdrawal_destination(
+        ctx: Context<UpdateTreasuryPolicy>,
+        destination: Pubkey,
+    ) -> Result<()> {
+        ctx.accounts
+            .config
+            .require_admin(&ctx.accounts.owner.to_account_info(), ctx.remaining_accounts)?;
+
+        let policy = &mut ctx.accounts.policy;
+        require!(
+            !policy.allowed_destinations.contains(&destination)
+                && policy.allowed_destinations.len() < MAX_WITHDRAWAL_DESTINATIONS,
+            AurumError::InvalidTreasuryPolicy
+        );
+        policy.allowed_destinations.push(destination);
+
+        msg!("Withdrawal destination added: {}", destination);
+        Ok(())
+    }
+
+    /// Remove a withdrawal destination from the allowlist (owner, or admin
+    /// multisig co-signers passed as remaining accounts)
+    pub fn remove_withdrawal_destination(
+        ctx: Context<UpdateTreasuryPolicy>,
+        destination: Pubkey,
+    ) -> Result<()> {
+        ctx.accounts
+            .config
+            .require_admin(&ctx.accounts.owner.to_account_info(), ctx.remaining_accounts)?;
+
+        let policy = &mut ctx.accounts.policy;
+        let len = policy.allowed_destinations.len();
+        policy.allowed_destinations.retain(|d| *d != destination);
+        require!(
+            policy.allowed_destinations.len() < len && !policy.allowed_destinations.is_empty(),
+            AurumError::InvalidTreasuryPolicy
+        );
+
+        msg!("Withdrawal destination removed: {}", destination);
+        Ok(())
+    }
+
+    /// Configure revenue share recipients (owner, or admin multisig co-signers
+    /// passed as remaining accounts). Every recipient must be on the treasury
+    /// policy allowlist.
+    pub fn set_fee_recipients(
+        ctx: Context<SetFeeRecipients>,
+        recipients: Vec<FeeRecipient>,
+    ) -> Result<()> {
+        ctx.accounts
+            .config
+            .require_admin(&ctx.accounts.owner.to_account_info(), ctx.remaining_accounts)?;
+        require!(
+            !recipients.is_empty() && recipients.len() <= MAX_FEE_RECIPIENTS,
+            AurumError::InvalidFeeRecipients
+        );
+        let total_bps = recipients
+            .iter()
+            .try_fold(0u64, |acc, r| {
+                require!(r.share_bps > 0, AurumError::InvalidFeeRecipients);
+                Ok(acc + r.share_bps as u64)
+            })?;
+        require!(total_bps == BPS_DENOMINATOR, AurumError::InvalidFeeRecipients);
+        for recipient in &recipients {
+            ctx.accounts.policy.check_destination(&recipient.recipient)?;
+        }
+
+        let revenue_share = &mut ctx.accounts.revenue_share;
+        revenue_share.version = RevenueShare::VERSION;
+        revenue_share.recipients = recipients;
+        revenue_share.bump = ctx.bumps.revenue_share;
+
+        msg!("Revenue share updated: {} recipients", revenue_share.recipients.len());
+        Ok(())
+    }
+
+    /// Distribute the treasury balance above rent to revenue share recipients.
+    /// Recipient accounts are passed as remaining accounts in configured order.
+    /// Every recipient must still be on the treasury policy allowlist, and at
+    /// most the epoch cap is distributed per epoch. Distributions are tracked
+    /// apart from withdrawals and have no cooldown, so they never block
+    /// `withdraw_fees`.
+    pub fn distribute_fees<'info>(
+        ctx: Context<'_, '_, 'info, 'info, DistributeFees<'info>>,
+    ) -> Result<()> {
+        let clock = Clock::get()?;
+        let recipients = &ctx.accounts.revenue_share.recipients;
+        require!(
+            ctx.remaining_accounts.len() == recipients.len(),
+            AurumError::FeeRecipientMismatch
+        );
+        let policy = &mut ctx.accounts.policy;
+        for recipient in recipients {
+            policy.check_destination(&recipient.recipient)?;
+        }
+
+        let treasury_info = ctx.accounts.treasury.to_account_info();
+        let available = Treasury::distributable(&treasury_info)?.min(policy.distributable_this_epoch(&clock));
+        let mut distributed: u64 = 0;
+
+        for (recipient, account) in recipients.iter().zip(ctx.remaining_accounts.iter()) {
+            require_keys_eq!(account.key(), recipient.recipient, AurumError::FeeRecipientMismatch);
+
+            let amount = ((available as u128)
+                .checked_mul(recipient.share_bps as u128)
+                .ok_or(AurumError::ArithmeticOverflow)?
+                / BPS_DENOMINATOR as u128) as u64;
+            if amount == 0 {
+                continue;
+            }
+
+            Treasury::pay_out(&treasury_info, account, amount)?;
+            distributed = distributed
+                .checked_add(amount)
+                .ok_or(AurumError::ArithmeticOverflow)?;
+
+            emit!(FeesDistributed {
+                recipient: recipient.recipient,
+                amount,
+                share_bps: recipient.share_bps,
+                timestamp: clock.unix_timestamp,
+            });
+        }
+
+        if distributed > 0 {
+            policy.record_distribution(distributed, &clock)?;
+        }
+
+        let treasury = &mut ctx.accounts.treasury;
+        treasury.total_distributed = treasury
+            .total_distributed
+            .checked_add(distributed)
+            .ok_or(AurumError::ArithmeticOverflow)?;
+
+        msg!("Fees distributed: {} lamports", distributed);
+        Ok(())
+    }
+
+    /// Register an integrator with a negotiated fee share (owner, or admin
+    /// multisig co-signers passed as remaining accounts)
+    pub fn register_integrator(
+        ctx: Context<RegisterIntegrator>,
+        payout: Pubkey,
+        share_bps: u16,
+    ) -> Result<()> {
+        ctx.accounts
+            .config
+            .require_admin(&ctx.accounts.owner.to_account_info(), ctx.remaining_accounts)?;
+        require!(
+            share_bps as u64 <= MAX_INTEGRATOR_SHARE_BPS,
+            AurumError::InvalidIntegratorShare
+        );
+
+        let integrator = &mut ctx.accounts.integrator;
+        integrator.version = Integrator::VERSION;
+        integrator.authority = ctx.accounts.authority.key();
+        integrator.payout = payout;
+        integrator.share_bps = share_bps;
+        integrator.total_volume = 0;
+        integrator.total_earned = 0;
+        integrator.operations = 0;
+        integrator.is_active = true;
+        integrator.bump = ctx.bumps.integrator;
+        integrator.reserved = [0; ACCOUNT_RESERVED_BYTES];
+
+        msg!("Integrator registered: {} ({} bps)", integrator.authority, share_bps);
+        Ok(())
+    }
+
+    /// Update an integrator's fee share, payout or status (owner, or admin
+    /// multisig co-signers passed as remaining accounts)
+    pub fn update_integrator(
+        ctx: Context<UpdateIntegrator>,
+        payout: Option<Pubkey>,
+        share_bps: Option<u16>,
+        is_active: Option<bool>,
+    ) -> Result<()> {
+        ctx.accounts
+            .config
+            .require_admin(&ctx.accounts.owner.to_account_info(), ctx.remaining_accounts)?;
+        let integrator = &mut ctx.accounts.integrator;
+
+        if let Some(payout) = payout {
+            integrator.payout = payout;
+        }
+        if let Some(bps) = share_bps {
+            require!(
+                bps as u64 <= MAX_INTEGRATOR_SHARE_BPS,
+                AurumError::InvalidIntegratorShare
+            );
+            integrator.share_bps = bps;
+        }
+        if let Some(active) = is_active {
+            integrator.is_active = active;
+        }
+
+        msg!("Integrator updated: {}", integrator.authority);
+        Ok(())
+    }
+
+    /// Upgrade the unversioned Config to the current layout. Authorized like
+    /// every admin instruction; a legacy Config has no signer set, so this is
+    /// the owner.
+    pub fn migrate_config(ctx: Context<MigrateConfig>) -> Result<()> {
+        let config_info = ctx.accounts.config.to_account_info();
+        let body = migration::legacy_body::<Config>(&config_info)?;
+
+        let config: Config = match config_info.data_len() {
+            ConfigV0::SPACE => ConfigV0::deserialize(&mut &body[..])
+                .map_err(|_| AurumError::InvalidAccountLayout)?
+                .into(),
+            Config::SPACE => return err!(AurumError::AlreadyMigrated),
+            _ => return err!(AurumError::InvalidAccountLayout),
+        };
+        config.require_admin(&ctx.accounts.owner.to_account_info(), ctx.remaining_accounts)?;
+
+        migration::rewrite_account(
+            &config_info,
+            &config,
+            Config::SPACE,
+            &ctx.accounts.owner.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+        )?;
+
+        msg!("Config migrated to version {}", Config::VERSION);
+        Ok(())
+    }
+
+    /// Upgrade an unversioned Treasury to the current layout (owner, or admin
+    /// multisig co-signers passed as remaining accounts). Also creates the
+    /// default treasury policy (owner allowlisted, no cap or cooldown) if the
+    /// deployment predates it, so `withdraw_fees` keeps working; this succeeds
+    /// even when the treasury is already current.
+    pub fn migrate_treasury(ctx: Context<MigrateTreasury>) -> Result<()> {
+        ctx.accounts
+            .config
+            .require_admin(&ctx.accounts.owner.to_account_info(), ctx.remaining_accounts)?;
+        let treasury_info = ctx.accounts.treasury.to_account_info();
+
+        if ctx.accounts.policy.version == 0 {
+            ctx.accounts
+                .policy
+                .set_default(ctx.accounts.config.owner, ctx.bumps.policy);
+            msg!("Default treasury policy created");
+
+            if treasury_info.data_len() == Treasury::SPACE {
+                let current = Treasury::try_deserialize(&mut &treasury_info.try_borrow_data()?[..])?;
+                if current.version == Treasury::VERSION {
+                    return Ok(());
+                }
+            }
+        }
+
+        let body = migration::legacy_body::<Treasury>(&treasury_info)?;
+
+        let treasury: Treasury = match treasury_info.data_len() {
+            TreasuryV0::SPACE => TreasuryV0::deserialize(&mut &body[..])
+                .map_err(|_| AurumError::InvalidAccountLayout)?
+                .into(),
+            Treasury::SPACE => return err!(AurumError::AlreadyMigrated),
+            _ => return err!(AurumError::InvalidAccountLayout),
+        };
+
+        migration::rewrite_account(
+            &treasury_info,
+            &treasury,
+            Treasury::SPACE,
+            &ctx.accounts.owner.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+        )?;
+
+        msg!("Treasury migrated to version {}", Treasury::VERSION);
+        Ok(())
+    }
+
+    /// Upgrade an unversioned ReferralAccount to the current layout.
+    /// Permissionless: data is preserved and the caller pays any extra rent.
+    pub fn migrate_referral(ctx: Context<MigrateReferral>) -> Result<()> {
+        let referral_info = ctx.accounts.referral_account.to_account_info();
+        let body = migration::legacy_body::<ReferralAccount>(&referral_info)?;
+
+        let referral: ReferralAccount = match referral_info.data_len() {
+            ReferralAccountV0::SPACE => ReferralAccountV0::deserialize(&mut &body[..])
+                .map_err(|_| AurumError::InvalidAccountLayout)?
+                .into(),
+            ReferralAccount::SPACE => return err!(AurumError::AlreadyMigrated),
+            _ => return err!(AurumError::InvalidAccountLayout),
+        };
+
+        let (expected_pda, _bump) = Pubkey::find_program_address(
+            &[REFERRAL_SEED, referral.referrer.as_ref()],
+            ctx.program_id,
+        );
+        require_keys_eq!(referral_info.key(), expected_pda, AurumError::InvalidReferralCode);
+
+        migration::rewrite_account(
+            &referral_info,
+            &referral,
+            ReferralAccount::SPACE,
+            &ctx.accounts.payer.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+        )?;
+
+        msg!("Referral account migrated to version {}", ReferralAccount::VERSION);
+        Ok(())
+    }
+}
+
+/// Create the PDA `account` with `space` bytes owned by `owner`, as Anchor's
+/// `init` does: an address that already holds lamports is topped up to rent
+/// exemption, allocated and assigned instead of created, so pre-funding it
+/// cannot block the instruction
+fn init_pda_account<'info>(
+    account: &AccountInfo<'info>,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    space: usize,
+    owner: &Pubkey,
+    seeds: &[&[u8]],
+) -> Result<()> {
+    let rent = Rent::get()?.minimum_balance(space);
+    let current_lamports = account.lamports();
+    if current_lamports == 0 {
+        return anchor_lang::system_program::create_account(
+            CpiContext::new_with_signer(
+                system_program.clone(),
+                anchor_lang::system_program::CreateAccount {
+                    from: payer.clone(),
+                    to: account.clone(),
+                },
+                &[seeds],
+            ),
+            rent,
+            space as u64,
+            owner,
+        );
+    }
+
+    let top_up = rent.saturating_sub(current_lamports);
+    if top_up > 0 {
+        anchor_lang::system_program::transfer(
+            CpiContext::new(
+                system_program.clone(),
+                anchor_lang::system_program::Transfer {
+                    from: payer.clone(),
+                    to: account.clone(),
+                },
+            ),
+            top_up,
+        )?;
+    }
+    anchor_lang::system_program::allocate(
+        CpiContext::new_with_signer(
+            system_program.clone(),
+            anchor_lang::system_program::Allocate {
+                account_to_allocate: account.clone(),
+            },
+            &[seeds],
+        ),
+        space as u64,
+    )?;
+    anchor_lang::system_program::assign(
+        CpiContext::new_with_signer(
+            system_program.clone(),
+            anchor_lang::system_program::Assign {
+                account_to_assign: account.clone(),
+            },
+            &[seeds],
+        ),
+        owner,
+    )
+}
+
+// Context structs
+
+#[derive(Accounts)]
+pub struct InitializeConfig<'info> {
+    #[account(
+        init,
+        payer = owner,
+        space = Config::SPACE,
+        seeds = [CONFIG_SEED],
+        bump
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        init,
+        payer = owner,
+        space = Treasury::SPACE,
+        seeds = [TREASURY_SEED],
+        bump
+    )]
+    pub treasury: Account<'info, Treasury>,
+
+    #[account(
+        init,
+        payer = owner,
+        space = TreasuryPolicy::SPACE,
+        seeds = [TREASURY_POLICY_SEED],
+        bump
+    )]
+    pub policy: Account<'info, TreasuryPolicy>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdatePricing<'info> {
+    #[account(mut, seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, Config>,
+    pub owner: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct QuoteFee<'info> {
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Box<Account<'info, Config>>,
+
+    /// CHECK: Wallet that would pay; the fee exemption, stake and credit
+    /// accounts must belong to it
+    pub payer: AccountInfo<'info>,
+
+    pub referral_account: Option<Account<'info, ReferralAccount>>,
+
+    #[account(seeds = [PROMO_SEED, promo_code.code.as_bytes()], bump = promo_code.bump)]
+    pub promo_code: Option<Account<'info, PromoCode>>,
+
+    #[account(
+        seeds = [INTEGRATOR_SEED, integrator.authority.as_ref()],
+        bump = integrator.bump
+    )]
+    pub integrator: Option<Account<'info, Integrator>>,
+
+    #[account(
+        seeds = [FEE_EXEMPTION_SEED, fee_exemption.wallet.as_ref()],
+        bump = fee_exemption.bump
+    )]
+    pub fee_exemption: Option<Account<'info, FeeExemption>>,
+
+    #[account(seeds = [STAKE_SEED, stake_account.owner.as_ref()], bump = stake_account.bump)]
+    pub stake_account: Option<Account<'info, StakeAccount>>,
+
+    #[account(
+        seeds = [CREDIT_SEED, credit_account.owner.as_ref(), &[credit_account.operation as u8]],
+        bump = credit_account.bump
+    )]
+    pub credit_account: Option<Account<'info, CreditAccount>>,
+}
+
+#[derive(Accounts)]
+pub struct SetAdminSigners<'info> {
+    #[account(mut, seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, Config>,
+    pub owner: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CreateProposal<'info> {
+    #[account(mut, seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        init,
+        payer = proposer,
+        space = Proposal::SPACE,
+        seeds = [PROPOSAL_SEED, config.proposal_count.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub proposal: Account<'info, Proposal>,
+
+    #[account(mut)]
+    pub proposer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ApproveProposal<'info> {
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        mut,
+        seeds = [PROPOSAL_SEED, proposal.id.to_le_bytes().as_ref()],
+        bump = proposal.bump
+    )]
+    pub proposal: Account<'info, Proposal>,
+
+    pub approver: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ExecuteProposal<'info> {
+    #[account(mut, seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        mut,
+        seeds = [PROPOSAL_SEED, proposal.id.to_le_bytes().as_ref()],
+        bump = proposal.bump
+    )]
+    pub proposal: Account<'info, Proposal>,
+
+    #[account(mut, seeds = [TREASURY_SEED], bump = treasury.bump)]
+    pub treasury: Account<'info, Treasury>,
+
+    /// Required for `WithdrawFees` actions
+    #[account(mut, seeds = [TREASURY_POLICY_SEED], bump = policy.bump)]
+    pub policy: Option<Account<'info, TreasuryPolicy>>,
+
+    /// CHECK: Must match the destination of a `WithdrawFees` action
+    #[account(mut)]
+    pub destination: Option<AccountInfo<'info>>,
+
+    pub executor: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CancelProposal<'info> {
+    #[account(
+        mut,
+        close = proposer,
+        seeds = [PROPOSAL_SEED, proposal.id.to_le_bytes().as_ref()],
+        bump = proposal.bump,
+        has_one = proposer @ AurumError::Unauthorized
+    )]
+    pub proposal: Account<'info, Proposal>,
+
+    #[account(mut)]
+    pub proposer: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CreateReferral<'info> {
+    #[account(
+        init,
+        payer = user,
+        space = ReferralAccount::SPACE,
+        seeds = [REFERRAL_SEED, user.key().as_ref()],
+        bump
+    )]
+    pub referral_account: Account<'info, ReferralAccount>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateReferral<'info> {
+    #[account(
+        mut,
+        seeds = [REFERRAL_SEED, referrer.key().as_ref()],
+        bump = referral_account.bump,
+        has_one = referrer @ AurumError::Unauthorized
+    )]
+    pub referral_account: Account<'info, ReferralAccount>,
+    pub referrer: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct DeactivateReferral<'info> {
+    #[account(
+        mut,
+        seeds = [REFERRAL_SEED, referral_account.referrer.as_ref()],
+        bump = referral_account.bump
+    )]
+    pub referral_account: Account<'info, ReferralAccount>,
+
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, Config>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CloseReferral<'info> {
+    #[account(
+        mut,
+        close = referrer,
+        seeds = [REFERRAL_SEED, referrer.key().as_ref()],
+        bump = referral_account.bump,
+        has_one = referrer @ AurumError::Unauthorized
+    )]
+    pub referral_account: Account<'info, ReferralAccount>,
+
+    #[account(mut)]
+    pub referrer: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(code: String)]
+pub struct CreatePromoCode<'info> {
+    #[account(
+        init,
+        payer = owner,
+        space = PromoCode::SPACE,
+        seeds = [PROMO_SEED, code.as_bytes()],
+        bump
+    )]
+    pub promo_code: Account<'info, PromoCode>,
+
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, Config>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdatePromoCode<'info> {
+    #[account(
+        mut,
+        seeds = [PROMO_SEED, promo_code.code.as_bytes()],
+        bump = promo_code.bump
+    )]
+    pub promo_code: Account<'info, PromoCode>,
+
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, Config>,
+
+    pub owner: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ClosePromoCode<'info> {
+    #[account(
+        mut,
+        close = owner,
+        seeds = [PROMO_SEED, promo_code.code.as_bytes()],
+        bump = promo_code.bump
+    )]
+    pub promo_code: Account<'info, PromoCode>,
+
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, Config>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(wallet: Pubkey)]
+pub struct CreateFeeExemption<'info> {
+    #[account(
+        init,
+        payer = owner,
+        space = FeeExemption::SPACE,
+        seeds = [FEE_EXEMPTION_SEED, wallet.as_ref()],
+        bump
+    )]
+    pub fee_exemption: Account<'info, FeeExemption>,
+
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, Config>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateFeeExemption<'info> {
+    #[account(
+        mut,
+        seeds = [FEE_EXEMPTION_SEED, fee_exemption.wallet.as_ref()],
+        bump = fee_exemption.bump
+    )]
+    pub fee_exemption: Account<'info, FeeExemption>,
+
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, Config>,
+
+    pub owner: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CloseFeeExemption<'info> {
+    #[account(
+        mut,
+        close = owner,
+        seeds = [FEE_EXEMPTION_SEED, fee_exemption.wallet.as_ref()],
+        bump = fee_exemption.bump
+    )]
+    pub fee_exemption: Account<'info, FeeExemption>,
+
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, Config>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(operation: OperationKind)]
+pub struct BuyCredits<'info> {
+    #[account(
+        init_if_needed,
+        payer = owner,
+        space = CreditAccount::SPACE,
+        seeds = [CREDIT_SEED, owner.key().as_ref(), &[operation as u8]],
+        bump
+    )]
+    pub credit_account: Account<'info, CreditAccount>,
+
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, Config>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RefundCredits<'info> {
+    #[account(
+        mut,
+        close = owner,
+        has_one = owner @ AurumError::InvalidCreditAccount,
+        seeds = [CREDIT_SEED, owner.key().as_ref(), &[credit_account.operation as u8]],
+        bump = credit_account.bump
+    )]
+    pub credit_account: Account<'info, CreditAccount>,
+
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, Config>,
+
+    #[account(mut, seeds = [TREASURY_SEED], bump = treasury.bump)]
+    pub treasury: Account<'info, Treasury>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeStaking<'info> {
+    #[account(
+        init,
+        payer = owner,
+        space = StakingPool::SPACE,
+        seeds = [STAKING_POOL_SEED],
+        bump
+    )]
+    pub staking_pool: Box<Account<'info, StakingPool>>,
+
+    pub mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        init,
+        payer = owner,
+        associated_token::mint = mint,
+        associated_token::authority = staking_pool
+    )]
+    pub vault: Box<Account<'info, TokenAccount>>,
+
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Box<Account<'info, Config>>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct Stake<'info> {
+    #[account(mut, seeds = [STAKING_POOL_SEED], bump = staking_pool.bump)]
+    pub staking_pool: Box<Account<'info, StakingPool>>,
+
+    #[account(
+        mut,
+        associated_token::mint = staking_pool.mint,
+        associated_token::authority = staking_pool
+    )]
+    pub vault: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        init_if_needed,
+        payer = owner,
+        space = StakeAccount::SPACE,
+        seeds = [STAKE_SEED, owner.key().as_ref()],
+        bump
+    )]
+    pub stake_account: Box<Account<'info, StakeAccount>>,
+
+    #[account(
+        mut,
+        token::mint = staking_pool.mint,
+        token::authority = owner
+    )]
+    pub owner_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct Unstake<'info> {
+    #[account(mut, seeds = [STAKING_POOL_SEED], bump = staking_pool.bump)]
+    pub staking_pool: Box<Account<'info, StakingPool>>,
+
+    #[account(
+        mut,
+        seeds = [STAKE_SEED, owner.key().as_ref()],
+        bump = stake_account.bump,
+        has_one = owner @ AurumError::InvalidStakeAccount
+    )]
+    pub stake_account: Box<Account<'info, StakeAccount>>,
+
+    pub owner: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawStake<'info> {
+    #[account(mut, seeds = [STAKING_POOL_SEED], bump = staking_pool.bump)]
+    pub staking_pool: Box<Account<'info, StakingPool>>,
+
+    #[account(
+        mut,
+        associated_token::mint = staking_pool.mint,
+        associated_token::authority = staking_pool
+    )]
+    pub vault: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        seeds = [STAKE_SEED, owner.key().as_ref()],
+        bump = stake_account.bump,
+        has_one = owner @ AurumError::InvalidStakeAccount
+    )]
+    pub stake_account: Box<Account<'info, StakeAccount>>,
+
+    #[account(
+        mut,
+        token::mint = staking_pool.mint,
+        token::authority = owner
+    )]
+    pub owner_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+/// Optional accounts that change the fee of a paid instruction. Every paid
+/// instruction embeds them as `fee_accounts`, so they all accept the same set.
+#[derive(Accounts)]
+pub struct OptionalFeeAccounts<'info> {
+    #[account(mut)]
+    pub referral_account: Option<Account<'info, ReferralAccount>>,
+
+    /// CHECK: Referrer to receive rebate
+    #[account(mut)]
+    pub referrer: Option<AccountInfo<'info>>,
+
+    /// Admin promo code
+    #[account(
+        mut,
+        seeds = [PROMO_SEED, promo_code.code.as_bytes()],
+        bump = promo_code.bump
+    )]
+    pub promo_code: Option<Account<'info, PromoCode>>,
+
+    /// Integrator (embedding frontend) receiving a share of the fee
+    #[account(
+        mut,
+        seeds = [INTEGRATOR_SEED, integrator.authority.as_ref()],
+        bump = integrator.bump
+    )]
+    pub integrator: Option<Account<'info, Integrator>>,
+
+    /// CHECK: Must match `integrator.payout`
+    #[account(mut)]
+    pub integrator_payout: Option<AccountInfo<'info>>,
+
+    /// Partner fee exemption; must belong to the payer
+    #[account(
+        mut,
+        seeds = [FEE_EXEMPTION_SEED, fee_exemption.wallet.as_ref()],
+        bump = fee_exemption.bump
+    )]
+    pub fee_exemption: Option<Account<'info, FeeExemption>>,
+
+    /// Prepaid credits; when present, one credit pays the fee
+    #[account(
+        mut,
+        seeds = [CREDIT_SEED, credit_account.owner.as_ref(), &[credit_account.operation as u8]],
+        bump = credit_account.bump
+    )]
+    pub credit_account: Option<Account<'info, CreditAccount>>,
+
+    /// Payer's stake, read for the staking discount tier
+    #[account(seeds = [STAKE_SEED, stake_account.owner.as_ref()], bump = stake_account.bump)]
+    pub stake_account: Option<Account<'info, StakeAccount>>,
+}
+
+#[derive(Accounts)]
+#[instruction(decimals: u8)]
+pub struct CreateToken<'info> {
+    #[account(
+        init,
+        payer = payer,
+        mint::decimals = decimals,
+        mint::authority = payer,
+    )]
+    pub mint: Account<'info, Mint>,
+
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, Config>,
+
+    #[account(mut, seeds = [TREASURY_SEED], bump = treasury.bump)]
+    pub treasury: Account<'info, Treasury>,
+
+    pub fee_accounts: OptionalFeeAccounts<'info>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+pub struct LockTokens<'info> {
+    pub mint: Account<'info, Mint>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = locker
+    )]
+    pub user_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        init_if_needed,
+        payer = locker,
+        associated_token::mint = mint,
+        associated_token::authority = treasury
+    )]
+    pub escrow_token_account: Account<'info, TokenAccount>,
+
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, Config>,
+
+    #[account(mut, seeds = [TREASURY_SEED], bump = treasury.bump)]
+    pub treasury: Account<'info, Treasury>,
+
+    pub fee_accounts: OptionalFeeAccounts<'info>,
+
+    #[account(mut)]
+    pub locker: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(lock_id: u64)]
+pub struct LockLpTokens<'info> {
+    pub mint: Box<Account<'info, Mint>>,
+
+    /// CHECK: AMM pool state; owner and layout are validated by `lp::validate_pool`
+    pub pool: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = locker
+    )]
+    pub user_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        init_if_needed,
+        payer = locker,
+        associated_token::mint = mint,
+        associated_token::authority = treasury
+    )]
+    pub escrow_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        init,
+        payer = locker,
+        space = LockRecord::SPACE,
+        seeds = [LOCK_SEED, mint.key().as_ref(), locker.key().as_ref(), lock_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub lock_record: Box<Account<'info, LockRecord>>,
+
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Box<Account<'info, Config>>,
+
+    #[account(mut, seeds = [TREASURY_SEED], bump = treasury.bump)]
+    pub treasury: Box<Account<'info, Treasury>>,
+
+    pub fee_accounts: OptionalFeeAccounts<'info>,
+
+    #[account(mut)]
+    pub locker: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct BatchLock<'info> {
+    pub mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = locker
+    )]
+    pub user_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        init_if_needed,
+        payer = locker,
+        associated_token::mint = mint,
+        associated_token::authority = treasury
+    )]
+    pub escrow_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Box<Account<'info, Config>>,
+
+    #[account(mut, seeds = [TREASURY_SEED], bump = treasury.bump)]
+    pub treasury: Box<Account<'info, Treasury>>,
+
+    pub fee_accounts: OptionalFeeAccounts<'info>,
+
+    #[account(mut)]
+    pub locker: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UnlockTokens<'info> {
+    pub mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        mut,
+        seeds = [LOCK_SEED, mint.key().as_ref(), locker.key().as_ref(), lock_record.lock_id.to_le_bytes().as_ref()],
+        bump = lock_record.bump,
+        has_one = mint,
+        has_one = locker,
+        has_one = beneficiary @ AurumError::Unauthorized
+    )]
+    pub lock_record: Box<Account<'info, LockRecord>>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = treasury
+    )]
+    pub escrow_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        init_if_needed,
+        payer = beneficiary,
+        associated_token::mint = mint,
+        associated_token::authority = beneficiary
+    )]
+    pub beneficiary_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(seeds = [TREASURY_SEED], bump = treasury.bump)]
+    pub treasury: Box<Account<'info, Treasury>>,
+
+    /// CHECK: Receives the lock record rent; checked by `has_one`
+    #[account(mut)]
+    pub locker: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub beneficiary: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct Multisend<'info> {
+    pub mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = sender
+    )]
+    pub sender_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Box<Account<'info, Config>>,
+
+    #[account(mut, seeds = [TREASURY_SEED], bump = treasury.bump)]
+    pub treasury: Box<Account<'info, Treasury>>,
+
+    pub fee_accounts: OptionalFeeAccounts<'info>,
+
+    #[account(mut)]
+    pub sender: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(distributor_id: u64)]
+pub struct CreateDistributor<'info> {
+    pub mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = Distributor::SPACE,
+        seeds = [DISTRIBUTOR_SEED, authority.key().as_ref(), distributor_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub distributor: Box<Account<'info, Distributor>>,
+
+    #[account(
+        init_if_needed,
+        payer = authority,
+        associated_token::mint = mint,
+        associated_token::authority = distributor
+    )]
+    pub vault: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = authority
+    )]
+    pub authority_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Box<Account<'info, Config>>,
+
+    #[account(mut, seeds = [TREASURY_SEED], bump = treasury.bump)]
+    pub treasury: Box<Account<'info, Treasury>>,
+
+    pub fee_accounts: OptionalFeeAccounts<'info>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(index: u64)]
+pub struct Claim<'info> {
+    pub mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        mut,
+        seeds = [DISTRIBUTOR_SEED, distributor.authority.as_ref(), distributor.distributor_id.to_le_bytes().as_ref()],
+        bump = distributor.bump,
+        has_one = mint,
+        has_one = vault
+    )]
+    pub distributor: Box<Account<'info, Distributor>>,
+
+    #[account(mut)]
+    pub vault: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        init,
+        payer = claimant,
+        space = ClaimStatus::SPACE,
+        seeds = [CLAIM_STATUS_SEED, distributor.key().as_ref(), index.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub claim_status: Box<Account<'info, ClaimStatus>>,
+
+    #[account(
+        init_if_needed,
+        payer = claimant,
+        associated_token::mint = mint,
+        associated_token::authority = claimant
+    )]
+    pub claimant_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(mut)]
+    pub claimant: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
 
-        // Transfer SOL from treasury to owner
-        let seeds = &[TREASURY_SEED, &[treasury.bump]];
-        let signer = &[&seeds[..]];
+#[derive(Accounts)]
+pub struct Clawback<'info> {
+    pub mint: Box<Account<'info, Mint>>,
 
-        let cpi_context = CpiContext::new_with_signer(
-            ctx.accounts.system_program.to_account_info(),
-            anchor_lang::system_program::Transfer {
-                from: ctx.accounts.treasury.to_account_info(),
-                to: ctx.accounts.owner.to_account_info(),
-            },
-            signer,
-        );
-        anchor_lang::system_program::transfer(cpi_context, amount)?;
+    #[account(
+        mut,
+        seeds = [DISTRIBUTOR_SEED, authority.key().as_ref(), distributor.distributor_id.to_le_bytes().as_ref()],
+        bump = distributor.bump,
+        has_one = mint,
+        has_one = vault,
+        has_one = authority @ AurumError::Unauthorized
+    )]
+    pub distributor: Box<Account<'info, Distributor>>,
 
-        emit!(FeesWithdrawn {
-            recipient: ctx.accounts.owner.key(),
-            amount,
-            timestamp: clock.unix_timestamp,
-        });
+    #[account(mut)]
+    pub vault: Box<Account<'info, TokenAccount>>,
 
-        msg!("Fees withdrawn: {} lamports to {}", amount, ctx.accounts.owner.key());
-        Ok(())
-    }
-}
+    #[account(
+        init_if_needed,
+        payer = authority,
+        associated_token::mint = mint,
+        associated_token::authority = authority
+    )]
+    pub authority_token_account: Box<Account<'info, TokenAccount>>,
 
-// Context structs
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
 
 #[derive(Accounts)]
-pub struct InitializeConfig<'info> {
+pub struct BurnTokens<'info> {
+    #[account(mut)]
+    pub mint: Account<'info, Mint>,
+
     #[account(
-        init,
-        payer = owner,
-        space = Config::SPACE,
-        seeds = [CONFIG_SEED],
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = burner
+    )]
+    pub user_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        init_if_needed,
+        payer = burner,
+        space = BurnStats::SPACE,
+        seeds = [BURN_STATS_SEED, mint.key().as_ref()],
         bump
     )]
-    pub config: Account<'info, Config>,
+    pub burn_stats: Box<Account<'info, BurnStats>>,
 
+    /// Optional proof-of-burn receipt for this burn, at sequence `burn_stats.burn_count`
     #[account(
         init,
-        payer = owner,
-        space = Treasury::SPACE,
-        seeds = [TREASURY_SEED],
+        payer = burner,
+        space = BurnReceipt::SPACE,
+        seeds = [BURN_RECEIPT_SEED, mint.key().as_ref(), burn_stats.burn_count.to_le_bytes().as_ref()],
         bump
     )]
+    pub burn_receipt: Option<Box<Account<'info, BurnReceipt>>>,
+
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, Config>,
+
+    #[account(mut, seeds = [TREASURY_SEED], bump = treasury.bump)]
     pub treasury: Account<'info, Treasury>,
 
+    pub fee_accounts: OptionalFeeAccounts<'info>,
+
     #[account(mut)]
-    pub owner: Signer<'info>,
+    pub burner: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
     pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-pub struct UpdatePricing<'info> {
+pub struct BurnAllAndClose<'info> {
+    #[account(mut)]
+    pub mint: Box<Account<'info, Mint>>,
+
     #[account(
         mut,
-        seeds = [CONFIG_SEED],
-        bump = config.bump,
-        has_one = owner @ AurumError::Unauthorized
+        associated_token::mint = mint,
+        associated_token::authority = burner
     )]
-    pub config: Account<'info, Config>,
-    pub owner: Signer<'info>,
-}
+    pub user_token_account: Box<Account<'info, TokenAccount>>,
 
-#[derive(Accounts)]
-pub struct CreateReferral<'info> {
     #[account(
-        init,
-        payer = user,
-        space = ReferralAccount::SPACE,
-        seeds = [REFERRAL_SEED, user.key().as_ref()],
+        init_if_needed,
+        payer = burner,
+        space = BurnStats::SPACE,
+        seeds = [BURN_STATS_SEED, mint.key().as_ref()],
         bump
     )]
-    pub referral_account: Account<'info, ReferralAccount>,
+    pub burn_stats: Box<Account<'info, BurnStats>>,
+
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Box<Account<'info, Config>>,
+
+    #[account(mut, seeds = [TREASURY_SEED], bump = treasury.bump)]
+    pub treasury: Box<Account<'info, Treasury>>,
+
+    pub fee_accounts: OptionalFeeAccounts<'info>,
 
     #[account(mut)]
-    pub user: Signer<'info>,
+    pub burner: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
     pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-#[instruction(decimals: u8)]
-pub struct CreateToken<'info> {
+pub struct CloseDustAccounts<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+#[instruction(schedule_id: u64)]
+pub struct CreateBurnSchedule<'info> {
+    pub mint: Box<Account<'info, Mint>>,
+
     #[account(
         init,
-        payer = payer,
-        mint::decimals = decimals,
-        mint::authority = payer,
+        payer = authority,
+        space = BurnSchedule::SPACE,
+        seeds = [BURN_SCHEDULE_SEED, mint.key().as_ref(), authority.key().as_ref(), schedule_id.to_le_bytes().as_ref()],
+        bump
     )]
-    pub mint: Account<'info, Mint>,
+    pub schedule: Box<Account<'info, BurnSchedule>>,
+
+    #[account(
+        init_if_needed,
+        payer = authority,
+        associated_token::mint = mint,
+        associated_token::authority = schedule
+    )]
+    pub vault: Box<Account<'info, TokenAccount>>,
 
     #[account(seeds = [CONFIG_SEED], bump = config.bump)]
-    pub config: Account<'info, Config>,
+    pub config: Box<Account<'info, Config>>,
 
     #[account(mut, seeds = [TREASURY_SEED], bump = treasury.bump)]
-    pub treasury: Account<'info, Treasury>,
+    pub treasury: Box<Account<'info, Treasury>>,
+
+    pub fee_accounts: OptionalFeeAccounts<'info>,
 
     #[account(mut)]
-    pub referral_account: Option<Account<'info, ReferralAccount>>,
+    pub authority: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct FundBurnSchedule<'info> {
+    pub mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        mut,
+        seeds = [BURN_SCHEDULE_SEED, mint.key().as_ref(), schedule.authority.as_ref(), schedule.schedule_id.to_le_bytes().as_ref()],
+        bump = schedule.bump,
+        has_one = mint,
+        has_one = vault
+    )]
+    pub schedule: Box<Account<'info, BurnSchedule>>,
 
-    /// CHECK: Referrer to receive rebate
     #[account(mut)]
-    pub referrer: Option<AccountInfo<'info>>,
+    pub vault: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = funder
+    )]
+    pub funder_token_account: Box<Account<'info, TokenAccount>>,
 
     #[account(mut)]
-    pub payer: Signer<'info>,
+    pub funder: Signer<'info>,
     pub token_program: Program<'info, Token>,
     pub system_program: Program<'info, System>,
-    pub rent: Sysvar<'info, Rent>,
 }
 
 #[derive(Accounts)]
-pub struct LockTokens<'info> {
-    pub mint: Account<'info, Mint>,
+pub struct CrankBurn<'info> {
+    #[account(mut)]
+    pub mint: Box<Account<'info, Mint>>,
 
     #[account(
         mut,
-        associated_token::mint = mint,
-        associated_token::authority = locker
+        seeds = [BURN_SCHEDULE_SEED, mint.key().as_ref(), schedule.authority.as_ref(), schedule.schedule_id.to_le_bytes().as_ref()],
+        bump = schedule.bump,
+        has_one = mint,
+        has_one = vault
     )]
-    pub user_token_account: Account<'info, TokenAccount>,
+    pub schedule: Box<Account<'info, BurnSchedule>>,
+
+    #[account(mut)]
+    pub vault: Box<Account<'info, TokenAccount>>,
 
     #[account(
         init_if_needed,
-        payer = locker,
-        associated_token::mint = mint,
-        associated_token::authority = treasury
+        payer = cranker,
+        space = BurnStats::SPACE,
+        seeds = [BURN_STATS_SEED, mint.key().as_ref()],
+        bump
     )]
-    pub escrow_token_account: Account<'info, TokenAccount>,
+    pub burn_stats: Box<Account<'info, BurnStats>>,
+
+    #[account(mut)]
+    pub cranker: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
 
+#[derive(Accounts)]
+pub struct WithdrawFees<'info> {
     #[account(seeds = [CONFIG_SEED], bump = config.bump)]
     pub config: Account<'info, Config>,
 
     #[account(mut, seeds = [TREASURY_SEED], bump = treasury.bump)]
     pub treasury: Account<'info, Treasury>,
 
-    #[account(mut)]
-    pub referral_account: Option<Account<'info, ReferralAccount>>,
+    #[account(mut, seeds = [TREASURY_POLICY_SEED], bump = policy.bump)]
+    pub policy: Account<'info, TreasuryPolicy>,
 
-    /// CHECK: Referrer to receive rebate
+    /// CHECK: Must be on the policy allowlist; defaults to `owner`
     #[account(mut)]
-    pub referrer: Option<AccountInfo<'info>>,
+    pub destination: Option<AccountInfo<'info>>,
 
     #[account(mut)]
-    pub locker: Signer<'info>,
-    pub token_program: Program<'info, Token>,
-    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub owner: Signer<'info>,
     pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-pub struct BurnTokens<'info> {
-    #[account(mut)]
-    pub mint: Account<'info, Mint>,
+pub struct SetTreasuryPolicy<'info> {
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, Config>,
 
     #[account(
-        mut,
-        associated_token::mint = mint,
-        associated_token::authority = burner
+        init_if_needed,
+        payer = owner,
+        space = TreasuryPolicy::SPACE,
+        seeds = [TREASURY_POLICY_SEED],
+        bump
     )]
-    pub user_token_account: Account<'info, TokenAccount>,
+    pub policy: Account<'info, TreasuryPolicy>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateTreasuryPolicy<'info> {
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, Config>,
+
+    #[account(mut, seeds = [TREASURY_POLICY_SEED], bump = policy.bump)]
+    pub policy: Account<'info, TreasuryPolicy>,
 
+    pub owner: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetFeeRecipients<'info> {
     #[account(seeds = [CONFIG_SEED], bump = config.bump)]
     pub config: Account<'info, Config>,
 
+    #[account(
+        init_if_needed,
+        payer = owner,
+        space = RevenueShare::SPACE,
+        seeds = [REVENUE_SHARE_SEED],
+        bump
+    )]
+    pub revenue_share: Account<'info, RevenueShare>,
+
+    #[account(seeds = [TREASURY_POLICY_SEED], bump = policy.bump)]
+    pub policy: Account<'info, TreasuryPolicy>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct DistributeFees<'info> {
+    #[account(seeds = [REVENUE_SHARE_SEED], bump = revenue_share.bump)]
+    pub revenue_share: Account<'info, RevenueShare>,
+
     #[account(mut, seeds = [TREASURY_SEED], bump = treasury.bump)]
     pub treasury: Account<'info, Treasury>,
 
-    #[account(mut)]
-    pub referral_account: Option<Account<'info, ReferralAccount>>,
+    #[account(mut, seeds = [TREASURY_POLICY_SEED], bump = policy.bump)]
+    pub policy: Account<'info, TreasuryPolicy>,
+}
+
+#[derive(Accounts)]
+pub struct RegisterIntegrator<'info> {
+    #[account(
+        init,
+        payer = owner,
+        space = Integrator::SPACE,
+        seeds = [INTEGRATOR_SEED, authority.key().as_ref()],
+        bump
+    )]
+    pub integrator: Account<'info, Integrator>,
+
+    /// CHECK: Integrator identity; only used as a PDA seed
+    pub authority: UncheckedAccount<'info>,
+
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, Config>,
 
-    /// CHECK: Referrer to receive rebate
     #[account(mut)]
-    pub referrer: Option<AccountInfo<'info>>,
+    pub owner: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateIntegrator<'info> {
+    #[account(
+        mut,
+        seeds = [INTEGRATOR_SEED, integrator.authority.as_ref()],
+        bump = integrator.bump
+    )]
+    pub integrator: Account<'info, Integrator>,
+
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, Config>,
+
+    pub owner: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct MigrateConfig<'info> {
+    /// CHECK: Old layout cannot be deserialized as `Config`; owner and discriminator are checked
+    #[account(mut, seeds = [CONFIG_SEED], bump)]
+    pub config: UncheckedAccount<'info>,
 
     #[account(mut)]
-    pub burner: Signer<'info>,
-    pub token_program: Program<'info, Token>,
-    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub owner: Signer<'info>,
     pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-pub struct WithdrawFees<'info> {
-    #[account(seeds = [CONFIG_SEED], bump = config.bump, has_one = owner @ AurumError::Unauthorized)]
+pub struct MigrateTreasury<'info> {
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
     pub config: Account<'info, Config>,
 
-    #[account(mut, seeds = [TREASURY_SEED], bump = treasury.bump)]
-    pub treasury: Account<'info, Treasury>,
+    /// CHECK: Old layout cannot be deserialized as `Treasury`; owner and discriminator are checked
+    #[account(mut, seeds = [TREASURY_SEED], bump)]
+    pub treasury: UncheckedAccount<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = owner,
+        space = TreasuryPolicy::SPACE,
+        seeds = [TREASURY_POLICY_SEED],
+        bump
+    )]
+    pub policy: Account<'info, TreasuryPolicy>,
 
     #[account(mut)]
     pub owner: Signer<'info>,
     pub system_program: Program<'info, System>,
 }
+
+#[derive(Accounts)]
+pub struct MigrateReferral<'info> {
+    /// CHECK: Old layout cannot be deserialized as `ReferralAccount`; owner, discriminator and PDA are checked
+    #[account(mut)]
+    pub referral_account: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
@@ -0,0 +1,109 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::AurumError;
+use crate::state::LpPoolInfo;
+
+/// Decodes the LP mint and pair mints from an AMM's pool account.
+/// Add a new AMM by implementing this trait and listing it in `KNOWN_AMMS`.
+pub trait PoolLayout {
+    /// Program that owns pool accounts of this layout
+    fn program_id(&self) -> Pubkey;
+
+    /// Extract `(lp_mint, mint_a, mint_b)` from raw pool account data
+    fn parse(&self, data: &[u8]) -> Option<(Pubkey, Pubkey, Pubkey)>;
+}
+
+/// Check the 8-byte Anchor account discriminator, `sha256("account:<Name>")[..8]`
+fn has_discriminator(data: &[u8], discriminator: &[u8; 8]) -> bool {
+    data.get(..8) == Some(&discriminator[..])
+}
+
+/// Read a pubkey at a fixed byte offset
+fn pubkey_at(data: &[u8], offset: usize) -> Option<Pubkey> {
+    data.get(offset..offset + 32)
+        .and_then(|bytes| <[u8; 32]>::try_from(bytes).ok())
+        .map(Pubkey::new_from_array)
+}
+
+/// Raydium AMM v4 (`LiquidityStateV4`, 752 bytes, no discriminator)
+pub struct RaydiumAmmV4;
+
+impl PoolLayout for RaydiumAmmV4 {
+    fn program_id(&self) -> Pubkey {
+        pubkey!("675kPX9MHTjS2zt1qfr1NYHuzeLXfQM9H24wFSUt1Mp8")
+    }
+
+    fn parse(&self, data: &[u8]) -> Option<(Pubkey, Pubkey, Pubkey)> {
+        if data.len() != 752 {
+            return None;
+        }
+        Some((pubkey_at(data, 464)?, pubkey_at(data, 400)?, pubkey_at(data, 432)?))
+    }
+}
+
+/// Raydium CPMM (`PoolState`, Anchor account)
+pub struct RaydiumCpmm;
+
+impl RaydiumCpmm {
+    /// Anchor discriminator of `PoolState`
+    const DISCRIMINATOR: [u8; 8] = [247, 237, 227, 245, 215, 195, 222, 70];
+}
+
+impl PoolLayout for RaydiumCpmm {
+    fn program_id(&self) -> Pubkey {
+        pubkey!("CPMMoo8L3F4NbTegBCKVNunggL7H1ZpdTHKxQB5qKP1R")
+    }
+
+    fn parse(&self, data: &[u8]) -> Option<(Pubkey, Pubkey, Pubkey)> {
+        if !has_discriminator(data, &Self::DISCRIMINATOR) {
+            return None;
+        }
+        // discriminator, amm_config, pool_creator, token_0_vault, token_1_vault,
+        // lp_mint, token_0_mint, token_1_mint
+        Some((pubkey_at(data, 136)?, pubkey_at(data, 168)?, pubkey_at(data, 200)?))
+    }
+}
+
+/// Meteora Dynamic AMM (`Pool`, Anchor account)
+pub struct MeteoraDynamicAmm;
+
+impl MeteoraDynamicAmm {
+    /// Anchor discriminator of `Pool`
+    const DISCRIMINATOR: [u8; 8] = [241, 154, 109, 4, 17, 177, 109, 188];
+}
+
+impl PoolLayout for MeteoraDynamicAmm {
+    fn program_id(&self) -> Pubkey {
+        pubkey!("Eo7WjKq67rjJQSZxS6z3YkapzY3eMj6Xy8X5EQVn5UaB")
+    }
+
+    fn parse(&self, data: &[u8]) -> Option<(Pubkey, Pubkey, Pubkey)> {
+        if !has_discriminator(data, &Self::DISCRIMINATOR) {
+            return None;
+        }
+        // discriminator, lp_mint, token_a_mint, token_b_mint
+        Some((pubkey_at(data, 8)?, pubkey_at(data, 40)?, pubkey_at(data, 72)?))
+    }
+}
+
+/// AMM layouts recognised by `lock_lp_tokens`
+pub const KNOWN_AMMS: &[&dyn PoolLayout] = &[&RaydiumAmmV4, &RaydiumCpmm, &MeteoraDynamicAmm];
+
+/// Check that `lp_mint` is the LP mint of `pool` and return the pool metadata
+pub fn validate_pool(pool: &AccountInfo, lp_mint: &Pubkey) -> Result<LpPoolInfo> {
+    let layout = KNOWN_AMMS
+        .iter()
+        .find(|layout| layout.program_id() == *pool.owner)
+        .ok_or(AurumError::UnsupportedPool)?;
+
+    let data = pool.try_borrow_data()?;
+    let (pool_lp_mint, mint_a, mint_b) = layout.parse(&data).ok_or(AurumError::UnsupportedPool)?;
+    require_keys_eq!(pool_lp_mint, *lp_mint, AurumError::LpMintMismatch);
+
+    Ok(LpPoolInfo {
+        amm_program: *pool.owner,
+        pool: pool.key(),
+        mint_a,
+        mint_b,
+    })
+}
@@ -0,0 +1,173 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::keccak::hashv;
+
+/// Leaf hashes are prefixed with 0 and node hashes with 1 so an internal node
+/// can never be passed off as a leaf
+const LEAF_PREFIX: &[u8] = &[0];
+const NODE_PREFIX: &[u8] = &[1];
+
+/// Hash of one claim: `keccak(0 || index || claimant || amount)`, integers little-endian
+pub fn leaf(index: u64, claimant: &Pubkey, amount: u64) -> [u8; 32] {
+    hashv(&[
+        LEAF_PREFIX,
+        &index.to_le_bytes(),
+        claimant.as_ref(),
+        &amount.to_le_bytes(),
+    ])
+    .to_bytes()
+}
+
+/// Hash of two sibling nodes, ordered so proofs need no direction bits
+pub fn hash_pair(a: &[u8; 32], b: &[u8; 32]) -> [u8; 32] {
+    let (low, high) = if a <= b { (a, b) } else { (b, a) };
+    hashv(&[NODE_PREFIX, low, high]).to_bytes()
+}
+
+/// Check that `leaf` is in the tree with `root`
+pub fn verify(proof: &[[u8; 32]], root: &[u8; 32], leaf: [u8; 32]) -> bool {
+    let computed = proof.iter().fold(leaf, |node, sibling| hash_pair(&node, sibling));
+    computed == *root
+}
+
+/// Claim tree built off-chain; an unpaired node is carried up to the next level
+#[cfg(not(target_os = "solana"))]
+pub struct MerkleTree {
+    levels: Vec<Vec<[u8; 32]>>,
+}
+
+#[cfg(not(target_os = "solana"))]
+impl MerkleTree {
+    /// Build the tree over `(claimant, amount)` claims; leaf `i` is claim index `i`
+    pub fn new(claims: &[(Pubkey, u64)]) -> Self {
+        let leaves: Vec<[u8; 32]> = claims
+            .iter()
+            .enumerate()
+            .map(|(index, (claimant, amount))| leaf(index as u64, claimant, *amount))
+            .collect();
+
+        let mut levels = vec![leaves];
+        while let Some(level) = levels.last().filter(|level| level.len() > 1) {
+            let next = level
+                .chunks(2)
+                .map(|pair| match pair {
+                    [a, b] => hash_pair(a, b),
+                    [a] => *a,
+                    _ => unreachable!(),
+                })
+                .collect();
+            levels.push(next);
+        }
+        Self { levels }
+    }
+
+    /// Tree root (all zeroes for an empty tree)
+    pub fn root(&self) -> [u8; 32] {
+        self.levels
+            .last()
+            .and_then(|level| level.first())
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Proof for claim `index`
+    pub fn proof(&self, index: usize) -> Vec<[u8; 32]> {
+        let mut proof = Vec::new();
+        let mut position = index;
+        for level in &self.levels[..self.levels.len() - 1] {
+            if let Some(sibling) = level.get(position ^ 1) {
+                proof.push(*sibling);
+            }
+            position /= 2;
+        }
+        proof
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn claims(count: u8) -> Vec<(Pubkey, u64)> {
+        (1..=count)
+            .map(|i| (Pubkey::new_from_array([i; 32]), i as u64 * 1_000))
+            .collect()
+    }
+
+    fn hex(bytes: &[u8; 32]) -> String {
+        bytes.iter().map(|b| format!("{:02x}", b)).collect()
+    }
+
+    #[test]
+    fn leaf_and_root_match_fixed_vectors() {
+        let claimant = Pubkey::new_from_array([1; 32]);
+        assert_eq!(
+            hex(&leaf(0, &claimant, 1_000)),
+            "78442e6ce86a2bd81ed3515e7c6a6a820eafb65d69c9dd079108b4026d6a9834"
+        );
+        assert_eq!(
+            hex(&MerkleTree::new(&claims(5)).root()),
+            "384cc1baa9308b3940a6a77e26617f649e31736df53136ae4824dda34e93affa"
+        );
+    }
+
+    #[test]
+    fn hash_pair_is_order_independent() {
+        let (a, b) = ([1; 32], [2; 32]);
+        assert_eq!(hash_pair(&a, &b), hash_pair(&b, &a));
+        assert_ne!(hash_pair(&a, &b), leaf(0, &Pubkey::new_from_array(a), 0));
+    }
+
+    #[test]
+    fn odd_leaf_is_carried_up() {
+        let claims = claims(5);
+        let leaves: Vec<[u8; 32]> = claims
+            .iter()
+            .enumerate()
+            .map(|(i, (claimant, amount))| leaf(i as u64, claimant, *amount))
+            .collect();
+        let left = hash_pair(&hash_pair(&leaves[0], &leaves[1]), &hash_pair(&leaves[2], &leaves[3]));
+        let tree = MerkleTree::new(&claims);
+
+        assert_eq!(tree.root(), hash_pair(&left, &leaves[4]));
+        // The carried leaf skips the levels where it had no sibling
+        assert_eq!(tree.proof(4), vec![left]);
+        assert_eq!(tree.proof(0).len(), 3);
+    }
+
+    #[test]
+    fn every_proof_verifies() {
+        for count in 1..=9 {
+            let claims = claims(count);
+            let tree = MerkleTree::new(&claims);
+            let root = tree.root();
+            for (index, (claimant, amount)) in claims.iter().enumerate() {
+                let proof = tree.proof(index);
+                assert!(verify(&proof, &root, leaf(index as u64, claimant, *amount)));
+                // Wrong amount, wrong index or wrong claimant are rejected
+                assert!(!verify(&proof, &root, leaf(index as u64, claimant, amount + 1)));
+                assert!(!verify(&proof, &root, leaf(index as u64 + 1, claimant, *amount)));
+                assert!(!verify(&proof, &root, leaf(index as u64, &Pubkey::default(), *amount)));
+            }
+        }
+    }
+
+    #[test]
+    fn tampered_proof_is_rejected() {
+        let claims = claims(4);
+        let tree = MerkleTree::new(&claims);
+        let leaf = leaf(1, &claims[1].0, claims[1].1);
+        let mut proof = tree.proof(1);
+        proof[0][0] ^= 1;
+        assert!(!verify(&proof, &tree.root(), leaf));
+        assert!(!verify(&tree.proof(1)[1..], &tree.root(), leaf));
+    }
+
+    #[test]
+    fn single_and_empty_trees() {
+        let claims = claims(1);
+        let tree = MerkleTree::new(&claims);
+        assert_eq!(tree.root(), leaf(0, &claims[0].0, claims[0].1));
+        assert!(tree.proof(0).is_empty());
+        assert_eq!(MerkleTree::new(&[]).root(), [0; 32]);
+    }
+}
@@ -0,0 +1,291 @@
+use anchor_lang::prelude::*;
+use anchor_lang::Discriminator;
+
+use crate::constants::*;
+use crate::errors::AurumError;
+use crate::state::*;
+
+/// Config as originally deployed (no version byte)
+#[derive(AnchorDeserialize)]
+pub struct ConfigV0 {
+    pub owner: Pubkey,
+    pub create_fee: u64,
+    pub lock_fee: u64,
+    pub burn_fee: u64,
+    pub referral_discount: u64,
+    pub referral_rebate: u64,
+    pub premium_anchor_price: u64,
+    pub bump: u8,
+}
+
+impl ConfigV0 {
+    pub const SPACE: usize = 8 + 32 + 8 + 8 + 8 + 8 + 8 + 8 + 1;
+}
+
+impl From<ConfigV0> for Config {
+    fn from(old: ConfigV0) -> Self {
+        Config {
+            version: Config::VERSION,
+            owner: old.owner,
+            create_fee: old.create_fee,
+            lock_fee: old.lock_fee,
+            burn_fee: old.burn_fee,
+            referral_discount: old.referral_discount,
+            referral_rebate: old.referral_rebate,
+            premium_anchor_price: old.premium_anchor_price,
+            admin_threshold: 0,
+            admin_signers: Vec::new(),
+            admin_set_seq: 0,
+            proposal_count: 0,
+            bump: old.bump,
+            multisend_flat_fee: DEFAULT_MULTISEND_FLAT_FEE,
+            multisend_fee_per_recipient: DEFAULT_MULTISEND_FEE_PER_RECIPIENT,
+            credit_discount_bps: 0,
+            credit_refund_fee_bps: 0,
+            stake_tiers: [StakeTier::default(); MAX_STAKE_TIERS],
+            reserved: [0; ACCOUNT_RESERVED_BYTES - 60],
+        }
+    }
+}
+
+/// Treasury as originally deployed: `total_collected` and `bump`
+#[derive(AnchorDeserialize)]
+pub struct TreasuryV0 {
+    pub total_collected: u64,
+    pub bump: u8,
+}
+
+impl TreasuryV0 {
+    pub const SPACE: usize = 8 + 8 + 1;
+}
+
+impl From<TreasuryV0> for Treasury {
+    fn from(old: TreasuryV0) -> Self {
+        Treasury {
+            version: Treasury::VERSION,
+            total_collected: old.total_collected,
+            total_rebated: 0,
+            total_withdrawn: 0,
+            total_distributed: 0,
+            creates: 0,
+            create_fees: 0,
+            locks: 0,
+            lock_fees: 0,
+            burns: 0,
+            burn_fees: 0,
+            bump: old.bump,
+            multisends: 0,
+            multisend_fees: 0,
+            reserved: [0; ACCOUNT_RESERVED_BYTES - 16],
+        }
+    }
+}
+
+/// ReferralAccount as originally deployed: no expiry, usage cap or version
+#[derive(AnchorDeserialize)]
+pub struct ReferralAccountV0 {
+    pub referrer: Pubkey,
+    pub code: String,
+    pub total_referrals: u64,
+    pub rebate_earned: u64,
+    pub bump: u8,
+}
+
+impl ReferralAccountV0 {
+    pub const SPACE: usize = 8 + 32 + 4 + 10 + 8 + 8 + 1;
+}
+
+// The migrations tell the legacy layout from the current one by account size
+// alone, so the sizes must differ
+const _: () = assert!(ConfigV0::SPACE != Config::SPACE);
+const _: () = assert!(TreasuryV0::SPACE != Treasury::SPACE);
+const _: () = assert!(ReferralAccountV0::SPACE != ReferralAccount::SPACE);
+
+impl From<ReferralAccountV0> for ReferralAccount {
+    fn from(old: ReferralAccountV0) -> Self {
+        ReferralAccount {
+            version: ReferralAccount::VERSION,
+            referrer: old.referrer,
+            code: old.code,
+            total_referrals: old.total_referrals,
+            rebate_earned: old.rebate_earned,
+            expires_at: None,
+            max_uses: None,
+            is_active: true,
+            bump: old.bump,
+            reserved: [0; ACCOUNT_RESERVED_BYTES],
+        }
+    }
+}
+
+/// Read the body of a program-owned account after checking its discriminator
+pub fn legacy_body<T: Discriminator>(account: &AccountInfo) -> Result<Vec<u8>> {
+    require_keys_eq!(*account.owner, crate::ID, AurumError::Unauthorized);
+    let data = account.try_borrow_data()?;
+    require!(
+        data.len() >= 8 && data[..8] == T::DISCRIMINATOR,
+        AurumError::InvalidAccountLayout
+    );
+    Ok(data[8..].to_vec())
+}
+
+/// Resize `account` to `new_len`, topping up rent from `payer`, and write `value`
+pub fn rewrite_account<'info, T: AccountSerialize>(
+    account: &AccountInfo<'info>,
+    value: &T,
+    new_len: usize,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+) -> Result<()> {
+    let required = Rent::get()?.minimum_balance(new_len);
+    let top_up = required.saturating_sub(account.lamports());
+    if top_up > 0 {
+        let cpi_context = CpiContext::new(
+            system_program.clone(),
+            anchor_lang::system_program::Transfer {
+                from: payer.clone(),
+                to: account.clone(),
+            },
+        );
+        anchor_lang::system_program::transfer(cpi_context, top_up)?;
+    }
+
+    account.realloc(new_len, true)?;
+    let mut data = account.try_borrow_mut_data()?;
+    data.fill(0);
+    value.try_serialize(&mut &mut data[..])?;
+    Ok(())
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const OWNER: Pubkey = Pubkey::new_from_array([7; 32]);
+
+    /// Legacy account bytes: discriminator, the given body, zero padding to `len`
+    fn legacy<T: Discriminator>(body: &[&[u8]], len: usize) -> Vec<u8> {
+        let mut data = T::DISCRIMINATOR.to_vec();
+        for field in body {
+            data.extend_from_slice(field);
+        }
+        assert!(data.len() <= len, "body longer than the legacy SPACE");
+        data.resize(len, 0);
+        data
+    }
+
+    fn with_account<R>(data: &mut [u8], owner: &Pubkey, f: impl FnOnce(&AccountInfo) -> R) -> R {
+        let key = Pubkey::new_unique();
+        let mut lamports = 0;
+        let info = AccountInfo::new(&key, false, true, &mut lamports, data, owner, false, 0);
+        f(&info)
+    }
+
+    /// Serialize `value` into a buffer of `space` bytes and read it back
+    fn round_trip<T: AccountSerialize + AccountDeserialize>(value: &T, space: usize) -> T {
+        let mut data = vec![0; space];
+        value.try_serialize(&mut &mut data[..]).unwrap();
+        T::try_deserialize(&mut &data[..]).unwrap()
+    }
+
+    /// Unversioned config body: owner, the six prices, bump
+    fn config_v0_body() -> Vec<u8> {
+        let mut body = OWNER.to_bytes().to_vec();
+        for value in [600u64, 300, 150, 100, 50, 1_200] {
+            body.extend_from_slice(&value.to_le_bytes());
+        }
+        body.push(254);
+        body
+    }
+
+    #[test]
+    fn config_round_trip() {
+        let mut data = legacy::<Config>(&[&config_v0_body()], ConfigV0::SPACE);
+        assert_eq!(data.len(), 89);
+
+        let body = with_account(&mut data, &crate::ID, legacy_body::<Config>).unwrap();
+        let config: Config = ConfigV0::deserialize(&mut &body[..]).unwrap().into();
+        let config = round_trip(&config, Config::SPACE);
+
+        assert_eq!(config.version, Config::VERSION);
+        assert_eq!(config.owner, OWNER);
+        assert_eq!(
+            (config.create_fee, config.lock_fee, config.burn_fee),
+            (600, 300, 150)
+        );
+        assert_eq!(
+            (config.referral_discount, config.referral_rebate),
+            (100, 50)
+        );
+        assert_eq!(config.premium_anchor_price, 1_200);
+        assert_eq!(config.bump, 254);
+        assert!(!config.is_multisig());
+        assert!(config.admin_signers.is_empty());
+        // Multisend must not become free on migrated deployments
+        assert_eq!(config.multisend_flat_fee, DEFAULT_MULTISEND_FLAT_FEE);
+        assert_eq!(
+            config.multisend_fee_per_recipient,
+            DEFAULT_MULTISEND_FEE_PER_RECIPIENT
+        );
+        assert_eq!(config.credit_discount_bps, 0);
+        assert_eq!(config.stake_discount_bps(u64::MAX), 0);
+    }
+
+    #[test]
+    fn treasury_round_trip() {
+        let mut data = legacy::<Treasury>(&[&500u64.to_le_bytes(), &[253]], TreasuryV0::SPACE);
+        assert_eq!(data.len(), 17);
+
+        let body = with_account(&mut data, &crate::ID, legacy_body::<Treasury>).unwrap();
+        let treasury: Treasury = TreasuryV0::deserialize(&mut &body[..]).unwrap().into();
+        let treasury = round_trip(&treasury, Treasury::SPACE);
+
+        assert_eq!(treasury.version, Treasury::VERSION);
+        assert_eq!((treasury.total_collected, treasury.bump), (500, 253));
+        assert_eq!(treasury.total_rebated, 0);
+        assert_eq!(treasury.creates, 0);
+    }
+
+    #[test]
+    fn referral_round_trip() {
+        let code = b"ABCDEFGH";
+        let body: [&[u8]; 6] = [
+            &OWNER.to_bytes(),
+            &(code.len() as u32).to_le_bytes(),
+            code,
+            &3u64.to_le_bytes(),
+            &40u64.to_le_bytes(),
+            &[252],
+        ];
+        let mut data = legacy::<ReferralAccount>(&body, ReferralAccountV0::SPACE);
+        assert_eq!(data.len(), 71);
+
+        let body = with_account(&mut data, &crate::ID, legacy_body::<ReferralAccount>).unwrap();
+        let referral: ReferralAccount = ReferralAccountV0::deserialize(&mut &body[..])
+            .unwrap()
+            .into();
+        let referral = round_trip(&referral, ReferralAccount::SPACE);
+
+        assert_eq!(referral.version, ReferralAccount::VERSION);
+        assert_eq!(
+            (referral.referrer, referral.code.as_str()),
+            (OWNER, "ABCDEFGH")
+        );
+        assert_eq!((referral.total_referrals, referral.rebate_earned), (3, 40));
+        assert_eq!((referral.expires_at, referral.max_uses), (None, None));
+        assert!(referral.is_active);
+        assert_eq!(referral.bump, 252);
+    }
+
+    #[test]
+    fn legacy_body_checks_owner_and_discriminator() {
+        let body = config_v0_body();
+        let mut data = legacy::<Config>(&[&body], ConfigV0::SPACE);
+        let foreign = Pubkey::new_unique();
+        assert!(with_account(&mut data, &foreign, legacy_body::<Config>).is_err());
+
+        let mut data = legacy::<Treasury>(&[&body], ConfigV0::SPACE);
+        assert!(with_account(&mut data, &crate::ID, legacy_body::<Config>).is_err());
+    }
+}
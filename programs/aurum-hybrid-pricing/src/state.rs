@@ -1,7 +1,12 @@
 use anchor_lang::prelude::*;
 
+use crate::constants::*;
+use crate::errors::AurumError;
+
 #[account]
 pub struct Config {
+    /// Account layout version
+    pub version: u8,
     /// Program owner who can update pricing
     pub owner: Pubkey,
     /// Create token fee in lamports
@@ -16,13 +21,37 @@ pub struct Config {
     pub referral_rebate: u64,
     /// Premium anchor price for display
     pub premium_anchor_price: u64,
+    /// Approvals required for privileged actions (0 = single `owner` signer)
+    pub admin_threshold: u8,
+    /// Multisig member keys
+    pub admin_signers: Vec<Pubkey>,
+    /// Incremented whenever the signer set changes; invalidates open proposals
+    pub admin_set_seq: u32,
+    /// Number of proposals created (next proposal id)
+    pub proposal_count: u64,
     /// PDA bump
     pub bump: u8,
+    /// Flat fee per `multisend` instruction in lamports
+    pub multisend_flat_fee: u64,
+    /// Additional `multisend` fee per recipient in lamports
+    pub multisend_fee_per_recipient: u64,
+    /// Discount on prepaid credits in basis points of the base fee
+    pub credit_discount_bps: u16,
+    /// Fee kept by the treasury when unused credits are refunded, in basis points
+    pub credit_refund_fee_bps: u16,
+    /// Staking discount tiers by ascending minimum stake; unused tiers are zeroed
+    pub stake_tiers: [StakeTier; MAX_STAKE_TIERS],
+    /// Reserved for future fields
+    pub reserved: [u8; ACCOUNT_RESERVED_BYTES - 60],
 }
 
 impl Config {
+    /// Current account layout version
+    pub const VERSION: u8 = 1;
+
     /// Space needed for Config account
     pub const SPACE: usize = 8 + // discriminator
+        1 + // version
         32 + // owner
         8 + // create_fee
         8 + // lock_fee
@@ -30,11 +59,233 @@ impl Config {
         8 + // referral_discount
         8 + // referral_rebate
         8 + // premium_anchor_price
-        1; // bump
+        1 + // admin_threshold
+        4 + MAX_ADMIN_SIGNERS * 32 + // admin_signers
+        4 + // admin_set_seq
+        8 + // proposal_count
+        1 + // bump
+        8 + // multisend_flat_fee
+        8 + // multisend_fee_per_recipient
+        2 + // credit_discount_bps
+        2 + // credit_refund_fee_bps
+        MAX_STAKE_TIERS * StakeTier::SPACE + // stake_tiers
+        ACCOUNT_RESERVED_BYTES - 60; // reserved
+
+    /// Base fee for `operation`. `recipients` only prices `Multisend`: the
+    /// multisend recipient count, or `max_num_nodes` for `create_distributor`.
+    pub fn base_fee(&self, operation: OperationKind, recipients: u64) -> Result<u64> {
+        let fee = match operation {
+            OperationKind::Create => self.create_fee,
+            OperationKind::Lock => self.lock_fee,
+            OperationKind::Burn => self.burn_fee,
+            OperationKind::Multisend => self
+                .multisend_fee_per_recipient
+                .checked_mul(recipients)
+                .and_then(|fee| fee.checked_add(self.multisend_flat_fee))
+                .ok_or(AurumError::ArithmeticOverflow)?,
+        };
+        Ok(fee)
+    }
+
+    /// Price of `count` prepaid credits for `operation`: the base fee less
+    /// `credit_discount_bps`. Multisend is priced per recipient, so it has no
+    /// credits.
+    pub fn credit_price(&self, operation: OperationKind, count: u64) -> Result<u64> {
+        require!(
+            count > 0 && operation != OperationKind::Multisend,
+            AurumError::InvalidCreditPurchase
+        );
+        let full_price = (self.base_fee(operation, 0)? as u128)
+            .checked_mul(count as u128)
+            .ok_or(AurumError::ArithmeticOverflow)?;
+        let discount = full_price
+            .checked_mul(self.credit_discount_bps as u128)
+            .ok_or(AurumError::ArithmeticOverflow)?
+            / BPS_DENOMINATOR as u128;
+        let price = u64::try_from(full_price - discount).map_err(|_| AurumError::ArithmeticOverflow)?;
+        Ok(price)
+    }
+
+    /// Discount in basis points for a stake of `staked`: the highest tier
+    /// whose minimum it meets
+    pub fn stake_discount_bps(&self, staked: u64) -> u16 {
+        self.stake_tiers
+            .iter()
+            .filter(|tier| tier.discount_bps > 0 && staked >= tier.min_stake)
+            .map(|tier| tier.discount_bps)
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Whether privileged actions require M-of-N admin approval
+    pub fn is_multisig(&self) -> bool {
+        self.admin_threshold > 0
+    }
+
+    /// Check that a privileged action is authorized: by `owner` in single-signer
+    /// mode, or by at least `admin_threshold` distinct admin signers among
+    /// `authority` and `co_signers` in multisig mode.
+    pub fn require_admin(&self, authority: &AccountInfo, co_signers: &[AccountInfo]) -> Result<()> {
+        if !self.is_multisig() {
+            require!(authority.is_signer, AurumError::Unauthorized);
+            require_keys_eq!(authority.key(), self.owner, AurumError::Unauthorized);
+            return Ok(());
+        }
+
+        let mut approvals: Vec<Pubkey> = Vec::with_capacity(self.admin_signers.len());
+        let candidates = std::iter::once((authority.key, authority.is_signer))
+            .chain(co_signers.iter().map(|info| (info.key, info.is_signer)));
+        for (key, is_signer) in candidates {
+            if is_signer && self.admin_signers.contains(key) && !approvals.contains(key) {
+                approvals.push(*key);
+            }
+        }
+        require!(
+            approvals.len() >= self.admin_threshold as usize,
+            AurumError::MultisigThresholdNotMet
+        );
+        Ok(())
+    }
+
+    /// Apply a pricing update, leaving `None` fields unchanged
+    pub fn apply_pricing(&mut self, update: &PricingUpdate) {
+        if let Some(fee) = update.create_fee {
+            self.create_fee = fee;
+        }
+        if let Some(fee) = update.lock_fee {
+            self.lock_fee = fee;
+        }
+        if let Some(fee) = update.burn_fee {
+            self.burn_fee = fee;
+        }
+        if let Some(discount) = update.referral_discount {
+            self.referral_discount = discount;
+        }
+        if let Some(rebate) = update.referral_rebate {
+            self.referral_rebate = rebate;
+        }
+        if let Some(price) = update.premium_anchor_price {
+            self.premium_anchor_price = price;
+        }
+    }
+
+    /// Replace the admin signer set
+    pub fn set_admin_signers(&mut self, signers: Vec<Pubkey>, threshold: u8) -> Result<()> {
+        require!(signers.len() <= MAX_ADMIN_SIGNERS, AurumError::InvalidMultisig);
+        require!(threshold as usize <= signers.len(), AurumError::InvalidMultisig);
+        require!(threshold > 0 || signers.is_empty(), AurumError::InvalidMultisig);
+        for (i, signer) in signers.iter().enumerate() {
+            require!(!signers[..i].contains(signer), AurumError::InvalidMultisig);
+        }
+
+        self.admin_signers = signers;
+        self.admin_threshold = threshold;
+        self.admin_set_seq = self.admin_set_seq.wrapping_add(1);
+        Ok(())
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct PricingUpdate {
+    pub create_fee: Option<u64>,
+    pub lock_fee: Option<u64>,
+    pub burn_fee: Option<u64>,
+    pub referral_discount: Option<u64>,
+    pub referral_rebate: Option<u64>,
+    pub premium_anchor_price: Option<u64>,
+}
+
+/// Paid operation. Selects the base fee, the promo code scope flag and the
+/// treasury counters, and is reported in `FeeCollected`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OperationKind {
+    Create,
+    Lock,
+    Burn,
+    Multisend,
+}
+
+impl OperationKind {
+    /// Bit for this operation in `PromoCode.operations`
+    pub fn flag(self) -> u8 {
+        match self {
+            OperationKind::Create => PROMO_OP_CREATE,
+            OperationKind::Lock => PROMO_OP_LOCK,
+            OperationKind::Burn => PROMO_OP_BURN,
+            OperationKind::Multisend => PROMO_OP_MULTISEND,
+        }
+    }
+
+    /// Lowercase name, for logs and off-chain labels
+    pub fn name(self) -> &'static str {
+        match self {
+            OperationKind::Create => "create",
+            OperationKind::Lock => "lock",
+            OperationKind::Burn => "burn",
+            OperationKind::Multisend => "multisend",
+        }
+    }
+}
+
+/// Privileged action executed through a multisig proposal
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub enum AdminAction {
+    UpdatePricing(PricingUpdate),
+    WithdrawFees { amount: u64, destination: Pubkey },
+    SetAdminSigners { signers: Vec<Pubkey>, threshold: u8 },
+}
+
+impl AdminAction {
+    /// Serialized size of the largest variant
+    pub const SPACE: usize = 1 + 4 + MAX_ADMIN_SIGNERS * 32 + 1;
+}
+
+#[account]
+pub struct Proposal {
+    /// Account layout version
+    pub version: u8,
+    /// Sequential proposal id (PDA seed)
+    pub id: u64,
+    /// Admin signer who created the proposal
+    pub proposer: Pubkey,
+    /// Action to execute once approved
+    pub action: AdminAction,
+    /// Admin signers who approved
+    pub approvals: Vec<Pubkey>,
+    /// `Config.admin_set_seq` at creation; the proposal is void if it changes
+    pub admin_set_seq: u32,
+    /// Whether the action has been executed
+    pub executed: bool,
+    /// Creation timestamp
+    pub created_at: i64,
+    /// PDA bump
+    pub bump: u8,
+    /// Reserved for future fields
+    pub reserved: [u8; ACCOUNT_RESERVED_BYTES],
+}
+
+impl Proposal {
+    /// Current account layout version
+    pub const VERSION: u8 = 1;
+
+    /// Space needed for Proposal account
+    pub const SPACE: usize = 8 + // discriminator
+        1 + // version
+        8 + // id
+        32 + // proposer
+        AdminAction::SPACE + // action
+        4 + MAX_ADMIN_SIGNERS * 32 + // approvals
+        4 + // admin_set_seq
+        1 + // executed
+        8 + // created_at
+        1 + // bump
+        ACCOUNT_RESERVED_BYTES; // reserved
 }
 
 #[account]
 pub struct ReferralAccount {
+    /// Account layout version
+    pub version: u8,
     /// The wallet that generated this referral code
     pub referrer: Pubkey,
     /// Referral code (8-10 characters derived from pubkey)
@@ -43,31 +294,1174 @@ pub struct ReferralAccount {
     pub total_referrals: u64,
     /// Total rebate earned in lamports
     pub rebate_earned: u64,
+    /// Unix timestamp after which the code is no longer accepted
+    pub expires_at: Option<i64>,
+    /// Maximum number of uses (None = unlimited)
+    pub max_uses: Option<u64>,
+    /// Whether the code can still be used
+    pub is_active: bool,
     /// PDA bump
     pub bump: u8,
+    /// Reserved for future fields
+    pub reserved: [u8; ACCOUNT_RESERVED_BYTES],
 }
 
 impl ReferralAccount {
+    /// Current account layout version
+    pub const VERSION: u8 = 1;
+
     /// Space needed for ReferralAccount
     pub const SPACE: usize = 8 + // discriminator
+        1 + // version
         32 + // referrer
         4 + 10 + // code (String with max 10 chars)
         8 + // total_referrals
         8 + // rebate_earned
-        1; // bump
+        1 + 8 + // expires_at
+        1 + 8 + // max_uses
+        1 + // is_active
+        1 + // bump
+        ACCOUNT_RESERVED_BYTES; // reserved
+
+    /// Ensure the code can be applied at `now`
+    pub fn validate_usable(&self, now: i64) -> Result<()> {
+        require!(self.is_active, AurumError::ReferralInactive);
+        if let Some(expires_at) = self.expires_at {
+            require!(now < expires_at, AurumError::ReferralExpired);
+        }
+        if let Some(max_uses) = self.max_uses {
+            require!(self.total_referrals < max_uses, AurumError::ReferralUsageExceeded);
+        }
+        Ok(())
+    }
 }
 
 #[account]
 pub struct Treasury {
+    /// Account layout version
+    pub version: u8,
     /// Total fees collected
     pub total_collected: u64,
+    /// Total referral rebates paid out
+    pub total_rebated: u64,
+    /// Total withdrawn via `withdraw_fees`
+    pub total_withdrawn: u64,
+    /// Total paid out via `distribute_fees`
+    pub total_distributed: u64,
+    /// Number of paid token creations
+    pub creates: u64,
+    /// Fees collected from token creations
+    pub create_fees: u64,
+    /// Number of paid locks
+    pub locks: u64,
+    /// Fees collected from locks
+    pub lock_fees: u64,
+    /// Number of paid burns
+    pub burns: u64,
+    /// Fees collected from burns
+    pub burn_fees: u64,
     /// PDA bump
     pub bump: u8,
+    /// Number of paid multisends
+    pub multisends: u64,
+    /// Fees collected from multisends
+    pub multisend_fees: u64,
+    /// Reserved for future fields
+    pub reserved: [u8; ACCOUNT_RESERVED_BYTES - 16],
 }
 
 impl Treasury {
+    /// Current account layout version
+    pub const VERSION: u8 = 1;
+
     /// Space needed for Treasury account
     pub const SPACE: usize = 8 + // discriminator
+        1 + // version
         8 + // total_collected
-        1; // bump
+        8 + // total_rebated
+        8 + // total_withdrawn
+        8 + // total_distributed
+        8 + // creates
+        8 + // create_fees
+        8 + // locks
+        8 + // lock_fees
+        8 + // burns
+        8 + // burn_fees
+        1 + // bump
+        8 + // multisends
+        8 + // multisend_fees
+        ACCOUNT_RESERVED_BYTES - 16; // reserved
+
+    /// Count a paid operation and the fee the treasury received for it
+    pub fn record_operation(&mut self, operation: OperationKind, fee: u64) -> Result<()> {
+        let (count, fees) = match operation {
+            OperationKind::Create => (&mut self.creates, &mut self.create_fees),
+            OperationKind::Lock => (&mut self.locks, &mut self.lock_fees),
+            OperationKind::Burn => (&mut self.burns, &mut self.burn_fees),
+            OperationKind::Multisend => (&mut self.multisends, &mut self.multisend_fees),
+        };
+        *count = count.checked_add(1).ok_or(AurumError::ArithmeticOverflow)?;
+        *fees = fees.checked_add(fee).ok_or(AurumError::ArithmeticOverflow)?;
+        Ok(())
+    }
+
+    /// Lamports held above the rent-exempt minimum
+    pub fn distributable(treasury: &AccountInfo) -> Result<u64> {
+        let rent_exempt = Rent::get()?.minimum_balance(treasury.data_len());
+        Ok(treasury.lamports().saturating_sub(rent_exempt))
+    }
+
+    /// Move lamports out of the treasury. The treasury carries data, so the
+    /// system program cannot debit it; lamports are moved directly instead.
+    pub fn pay_out(treasury: &AccountInfo, to: &AccountInfo, amount: u64) -> Result<()> {
+        require!(
+            amount <= Self::distributable(treasury)?,
+            AurumError::InsufficientTreasuryBalance
+        );
+        **treasury.try_borrow_mut_lamports()? -= amount;
+        **to.try_borrow_mut_lamports()? = to
+            .lamports()
+            .checked_add(amount)
+            .ok_or(AurumError::ArithmeticOverflow)?;
+        Ok(())
+    }
+}
+
+/// Staking discount tier
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct StakeTier {
+    /// Staked tokens needed for this tier
+    pub min_stake: u64,
+    /// Discount on the base fee in basis points (0 = unused tier)
+    pub discount_bps: u16,
+}
+
+impl StakeTier {
+    pub const SPACE: usize = 8 + 2;
+
+    /// Validate a tier table and pad it to `MAX_STAKE_TIERS` with unused tiers
+    pub fn table(tiers: &[StakeTier]) -> Result<[StakeTier; MAX_STAKE_TIERS]> {
+        require!(tiers.len() <= MAX_STAKE_TIERS, AurumError::InvalidStakeTiers);
+        for (i, tier) in tiers.iter().enumerate() {
+            require!(
+                tier.discount_bps > 0 && tier.discount_bps as u64 <= MAX_STAKE_DISCOUNT_BPS,
+                AurumError::InvalidStakeTiers
+            );
+            if i > 0 {
+                let previous = &tiers[i - 1];
+                require!(
+                    tier.min_stake > previous.min_stake && tier.discount_bps >= previous.discount_bps,
+                    AurumError::InvalidStakeTiers
+                );
+            }
+        }
+        let mut table = [StakeTier::default(); MAX_STAKE_TIERS];
+        table[..tiers.len()].copy_from_slice(tiers);
+        Ok(table)
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FeeRecipient {
+    /// Wallet receiving this share
+    pub recipient: Pubkey,
+    /// Share of distributed fees in basis points
+    pub share_bps: u16,
+}
+
+#[account]
+pub struct RevenueShare {
+    /// Account layout version
+    pub version: u8,
+    /// Recipients and their shares; shares sum to 10_000
+    pub recipients: Vec<FeeRecipient>,
+    /// PDA bump
+    pub bump: u8,
+    /// Reserved for future fields
+    pub reserved: [u8; ACCOUNT_RESERVED_BYTES],
+}
+
+impl RevenueShare {
+    /// Current account layout version
+    pub const VERSION: u8 = 1;
+
+    /// Space needed for RevenueShare account
+    pub const SPACE: usize = 8 + // discriminator
+        1 + // version
+        4 + MAX_FEE_RECIPIENTS * (32 + 2) + // recipients
+        1 + // bump
+        ACCOUNT_RESERVED_BYTES; // reserved
+}
+
+#[account]
+pub struct PromoCode {
+    /// Account layout version
+    pub version: u8,
+    /// Campaign code, e.g. "LAUNCH50"
+    pub code: String,
+    /// Discount off the base fee in basis points (10_000 = 100%)
+    pub discount_bps: u16,
+    /// Bitmask of operations the code applies to (PROMO_OP_*)
+    pub operations: u8,
+    /// Whether the code can be combined with a referral code
+    pub allow_referral: bool,
+    /// Unix timestamp after which the code is no longer accepted
+    pub expires_at: Option<i64>,
+    /// Maximum number of uses (None = unlimited)
+    pub max_uses: Option<u64>,
+    /// Number of times the code has been used
+    pub uses: u64,
+    /// Whether the code can still be used
+    pub is_active: bool,
+    /// PDA bump
+    pub bump: u8,
+    /// Reserved for future fields
+    pub reserved: [u8; ACCOUNT_RESERVED_BYTES],
+}
+
+impl PromoCode {
+    /// Current account layout version
+    pub const VERSION: u8 = 1;
+
+    /// Space needed for PromoCode account
+    pub const SPACE: usize = 8 + // discriminator
+        1 + // version
+        4 + PROMO_CODE_MAX_LENGTH + // code
+        2 + // discount_bps
+        1 + // operations
+        1 + // allow_referral
+        1 + 8 + // expires_at
+        1 + 8 + // max_uses
+        8 + // uses
+        1 + // is_active
+        1 + // bump
+        ACCOUNT_RESERVED_BYTES; // reserved
+
+    /// Check that a new expiry lies after `now`
+    pub fn validate_expiry(expires_at: i64, now: i64) -> Result<()> {
+        require!(expires_at > now, AurumError::InvalidExpiry);
+        Ok(())
+    }
+
+    /// Validate the code for `operation` at `now` and return the discount on `base_fee`
+    pub fn discount_for(&self, base_fee: u64, operation: OperationKind, now: i64) -> Result<u64> {
+        require!(self.is_active, AurumError::PromoCodeInactive);
+        require!(self.operations & operation.flag() != 0, AurumError::PromoCodeNotApplicable);
+        if let Some(expires_at) = self.expires_at {
+            require!(now < expires_at, AurumError::PromoCodeExpired);
+        }
+        if let Some(max_uses) = self.max_uses {
+            require!(self.uses < max_uses, AurumError::PromoCodeUsageExceeded);
+        }
+
+        let discount = (base_fee as u128)
+            .checked_mul(self.discount_bps as u128)
+            .ok_or(AurumError::ArithmeticOverflow)?
+            / BPS_DENOMINATOR as u128;
+        Ok(discount as u64)
+    }
+}
+
+#[account]
+pub struct Integrator {
+    /// Account layout version
+    pub version: u8,
+    /// Integrator identity (PDA seed)
+    pub authority: Pubkey,
+    /// Wallet receiving the integrator's fee share
+    pub payout: Pubkey,
+    /// Share of the final fee in basis points
+    pub share_bps: u16,
+    /// Total fees paid through this integrator
+    pub total_volume: u64,
+    /// Total fee share paid to the integrator
+    pub total_earned: u64,
+    /// Number of paid operations routed through this integrator
+    pub operations: u64,
+    /// Whether the integrator can receive fee shares
+    pub is_active: bool,
+    /// PDA bump
+    pub bump: u8,
+    /// Reserved for future fields
+    pub reserved: [u8; ACCOUNT_RESERVED_BYTES],
+}
+
+impl Integrator {
+    /// Current account layout version
+    pub const VERSION: u8 = 1;
+
+    /// Space needed for Integrator account
+    pub const SPACE: usize = 8 + // discriminator
+        1 + // version
+        32 + // authority
+        32 + // payout
+        2 + // share_bps
+        8 + // total_volume
+        8 + // total_earned
+        8 + // operations
+        1 + // is_active
+        1 + // bump
+        ACCOUNT_RESERVED_BYTES; // reserved
+
+    /// Integrator's cut of `fee`
+    pub fn share_of(&self, fee: u64) -> Result<u64> {
+        let share = (fee as u128)
+            .checked_mul(self.share_bps as u128)
+            .ok_or(AurumError::ArithmeticOverflow)?
+            / BPS_DENOMINATOR as u128;
+        Ok(share as u64)
+    }
+
+    /// Record a routed operation
+    pub fn record(&mut self, volume: u64, share: u64) -> Result<()> {
+        self.total_volume = self
+            .total_volume
+            .checked_add(volume)
+            .ok_or(AurumError::ArithmeticOverflow)?;
+        self.total_earned = self
+            .total_earned
+            .checked_add(share)
+            .ok_or(AurumError::ArithmeticOverflow)?;
+        self.operations = self
+            .operations
+            .checked_add(1)
+            .ok_or(AurumError::ArithmeticOverflow)?;
+        Ok(())
+    }
+}
+
+#[account]
+pub struct FeeExemption {
+    /// Account layout version
+    pub version: u8,
+    /// Exempted wallet (PDA seed); must be the fee payer
+    pub wallet: Pubkey,
+    /// Discount off the base fee in basis points (10_000 = no fee)
+    pub discount_bps: u16,
+    /// Unix timestamp after which the exemption is no longer accepted
+    pub expires_at: Option<i64>,
+    /// Number of fees the exemption has been applied to
+    pub uses: u64,
+    /// Total lamports waived
+    pub total_waived: u64,
+    /// PDA bump
+    pub bump: u8,
+    /// Reserved for future fields
+    pub reserved: [u8; ACCOUNT_RESERVED_BYTES],
+}
+
+impl FeeExemption {
+    /// Current account layout version
+    pub const VERSION: u8 = 1;
+
+    /// Space needed for FeeExemption account
+    pub const SPACE: usize = 8 + // discriminator
+        1 + // version
+        32 + // wallet
+        2 + // discount_bps
+        1 + 8 + // expires_at
+        8 + // uses
+        8 + // total_waived
+        1 + // bump
+        ACCOUNT_RESERVED_BYTES; // reserved
+
+    /// Validate the exemption at `now` and return the discount on `base_fee`
+    pub fn discount_for(&self, base_fee: u64, now: i64) -> Result<u64> {
+        if let Some(expires_at) = self.expires_at {
+            require!(now < expires_at, AurumError::FeeExemptionExpired);
+        }
+        let discount = (base_fee as u128)
+            .checked_mul(self.discount_bps as u128)
+            .ok_or(AurumError::ArithmeticOverflow)?
+            / BPS_DENOMINATOR as u128;
+        Ok(discount as u64)
+    }
+
+    /// Record an exempted fee
+    pub fn record(&mut self, waived: u64) -> Result<()> {
+        self.uses = self.uses.checked_add(1).ok_or(AurumError::ArithmeticOverflow)?;
+        self.total_waived = self
+            .total_waived
+            .checked_add(waived)
+            .ok_or(AurumError::ArithmeticOverflow)?;
+        Ok(())
+    }
+}
+
+#[account]
+pub struct CreditAccount {
+    /// Account layout version
+    pub version: u8,
+    /// Wallet that bought the credits; only it can spend or refund them
+    pub owner: Pubkey,
+    /// Operation the credits pay for
+    pub operation: OperationKind,
+    /// Unused credits
+    pub credits: u64,
+    /// Lamports held for the unused credits, on top of rent
+    pub balance: u64,
+    /// Credits bought over the account's lifetime
+    pub purchased: u64,
+    /// Credits spent
+    pub used: u64,
+    /// PDA bump
+    pub bump: u8,
+    /// Reserved for future fields
+    pub reserved: [u8; ACCOUNT_RESERVED_BYTES],
+}
+
+impl CreditAccount {
+    /// Current account layout version
+    pub const VERSION: u8 = 1;
+
+    /// Space needed for CreditAccount account
+    pub const SPACE: usize = 8 + // discriminator
+        1 + // version
+        32 + // owner
+        1 + // operation
+        8 + // credits
+        8 + // balance
+        8 + // purchased
+        8 + // used
+        1 + // bump
+        ACCOUNT_RESERVED_BYTES; // reserved
+
+    /// Add `count` credits bought for `price` lamports
+    pub fn add(&mut self, count: u64, price: u64) -> Result<()> {
+        self.credits = self.credits.checked_add(count).ok_or(AurumError::ArithmeticOverflow)?;
+        self.purchased = self.purchased.checked_add(count).ok_or(AurumError::ArithmeticOverflow)?;
+        self.balance = self.balance.checked_add(price).ok_or(AurumError::ArithmeticOverflow)?;
+        Ok(())
+    }
+
+    /// Lamports the next credit spent is worth: the average price of the
+    /// unused credits, so the last one takes any rounding dust
+    pub fn next_value(&self) -> Result<u64> {
+        require!(self.credits > 0, AurumError::InsufficientCredits);
+        Ok(self.balance / self.credits)
+    }
+
+    /// Spend one credit and return the lamports it was worth (`next_value`)
+    pub fn spend(&mut self) -> Result<u64> {
+        let value = self.next_value()?;
+        self.credits -= 1;
+        self.balance -= value;
+        self.used = self.used.checked_add(1).ok_or(AurumError::ArithmeticOverflow)?;
+        Ok(value)
+    }
+
+    /// Move lamports out of a credit account. Like the treasury, it carries
+    /// data, so the system program cannot debit it.
+    pub fn pay_out(credit_account: &AccountInfo, to: &AccountInfo, amount: u64) -> Result<()> {
+        **credit_account.try_borrow_mut_lamports()? -= amount;
+        **to.try_borrow_mut_lamports()? = to
+            .lamports()
+            .checked_add(amount)
+            .ok_or(AurumError::ArithmeticOverflow)?;
+        Ok(())
+    }
+}
+
+#[account]
+pub struct StakingPool {
+    /// Account layout version
+    pub version: u8,
+    /// Token staked for fee discounts
+    pub mint: Pubkey,
+    /// Seconds between `unstake` and `withdraw_stake`
+    pub cooldown: i64,
+    /// Tokens currently staked
+    pub total_staked: u64,
+    /// Tokens unstaked and cooling down
+    pub total_unstaking: u64,
+    /// PDA bump
+    pub bump: u8,
+    /// Reserved for future fields
+    pub reserved: [u8; ACCOUNT_RESERVED_BYTES],
+}
+
+impl StakingPool {
+    /// Current account layout version
+    pub const VERSION: u8 = 1;
+
+    /// Space needed for StakingPool account
+    pub const SPACE: usize = 8 + // discriminator
+        1 + // version
+        32 + // mint
+        8 + // cooldown
+        8 + // total_staked
+        8 + // total_unstaking
+        1 + // bump
+        ACCOUNT_RESERVED_BYTES; // reserved
+}
+
+#[account]
+pub struct StakeAccount {
+    /// Account layout version
+    pub version: u8,
+    /// Staker; the discount applies to fees this wallet pays
+    pub owner: Pubkey,
+    /// Tokens staked; those older than `MIN_STAKE_AGE` count toward a discount tier
+    pub amount: u64,
+    /// Tokens unstaked and cooling down
+    pub unstaking: u64,
+    /// When the unstaking tokens can be withdrawn
+    pub unstake_available_at: i64,
+    /// PDA bump
+    pub bump: u8,
+    /// Part of `amount` that counts toward a tier only from
+    /// `recent_since + MIN_STAKE_AGE`
+    pub recent: u64,
+    /// When `recent` was last added to
+    pub recent_since: i64,
+    /// Reserved for future fields
+    pub reserved: [u8; ACCOUNT_RESERVED_BYTES - 16],
+}
+
+impl StakeAccount {
+    /// Current account layout version (2 carved `recent` and `recent_since`
+    /// out of the reserved bytes; zero values keep version 1 semantics)
+    pub const VERSION: u8 = 2;
+
+    /// Space needed for StakeAccount account
+    pub const SPACE: usize = 8 + // discriminator
+        1 + // version
+        32 + // owner
+        8 + // amount
+        8 + // unstaking
+        8 + // unstake_available_at
+        1 + // bump
+        8 + // recent
+        8 + // recent_since
+        ACCOUNT_RESERVED_BYTES - 16; // reserved
+
+    /// Whether the recently staked tokens have reached `MIN_STAKE_AGE` at `now`
+    fn recent_matured(&self, now: i64) -> bool {
+        now >= self.recent_since.saturating_add(MIN_STAKE_AGE)
+    }
+
+    /// Staked tokens that count toward a discount tier at `now`
+    pub fn eligible_amount(&self, now: i64) -> u64 {
+        if self.recent_matured(now) {
+            self.amount
+        } else {
+            self.amount.saturating_sub(self.recent)
+        }
+    }
+
+    /// Add `amount` staked at `now`. Recently staked tokens that have matured
+    /// are folded into the eligible balance first; the rest restart the age.
+    pub fn add_stake(&mut self, amount: u64, now: i64) -> Result<()> {
+        if self.recent_matured(now) {
+            self.recent = 0;
+        }
+        self.recent = self
+            .recent
+            .checked_add(amount)
+            .ok_or(AurumError::ArithmeticOverflow)?;
+        self.recent_since = now;
+        self.amount = self
+            .amount
+            .checked_add(amount)
+            .ok_or(AurumError::ArithmeticOverflow)?;
+        Ok(())
+    }
+
+    /// Remove `amount` from the stake. Eligible tokens are removed first, so
+    /// unstaking never makes recent tokens eligible early.
+    pub fn remove_stake(&mut self, amount: u64) -> Result<()> {
+        require!(
+            amount > 0 && amount <= self.amount,
+            AurumError::InvalidStakeAmount
+        );
+        self.amount -= amount;
+        self.recent = self.recent.min(self.amount);
+        Ok(())
+    }
+}
+
+#[account]
+pub struct TreasuryPolicy {
+    /// Account layout version
+    pub version: u8,
+    /// Wallets `withdraw_fees` may pay out to
+    pub allowed_destinations: Vec<Pubkey>,
+    /// Maximum lamports withdrawn per epoch (0 = no cap)
+    pub epoch_cap: u64,
+    /// Minimum seconds between withdrawals (0 = no cooldown)
+    pub cooldown_seconds: i64,
+    /// Epoch of the current withdrawal window
+    pub current_epoch: u64,
+    /// Lamports withdrawn in `current_epoch`
+    pub withdrawn_this_epoch: u64,
+    /// Timestamp of the last withdrawal
+    pub last_withdrawal_at: i64,
+    /// PDA bump
+    pub bump: u8,
+    /// Lamports paid out by `distribute_fees` in `current_epoch`, capped by
+    /// `epoch_cap` separately from withdrawals
+    pub distributed_this_epoch: u64,
+    /// Reserved for future fields
+    pub reserved: [u8; ACCOUNT_RESERVED_BYTES - 8],
+}
+
+impl TreasuryPolicy {
+    /// Current account layout version
+    pub const VERSION: u8 = 1;
+
+    /// Space needed for TreasuryPolicy account
+    pub const SPACE: usize = 8 + // discriminator
+        1 + // version
+        4 + MAX_WITHDRAWAL_DESTINATIONS * 32 + // allowed_destinations
+        8 + // epoch_cap
+        8 + // cooldown_seconds
+        8 + // current_epoch
+        8 + // withdrawn_this_epoch
+        8 + // last_withdrawal_at
+        1 + // bump
+        8 + // distributed_this_epoch
+        ACCOUNT_RESERVED_BYTES - 8; // reserved
+
+    /// Reset to the default policy: only `owner` allowlisted, no cap and no
+    /// cooldown, which matches withdrawals before the policy existed
+    pub fn set_default(&mut self, owner: Pubkey, bump: u8) {
+        self.version = Self::VERSION;
+        self.allowed_destinations = vec![owner];
+        self.epoch_cap = 0;
+        self.cooldown_seconds = 0;
+        self.current_epoch = 0;
+        self.withdrawn_this_epoch = 0;
+        self.last_withdrawal_at = 0;
+        self.bump = bump;
+        self.distributed_this_epoch = 0;
+        self.reserved = [0; ACCOUNT_RESERVED_BYTES - 8];
+    }
+
+    /// Enforce the allowlist, cooldown and epoch cap, then record the withdrawal
+    pub fn record_withdrawal(&mut self, destination: &Pubkey, amount: u64, clock: &Clock) -> Result<()> {
+        self.check_destination(destination)?;
+        if self.cooldown_seconds > 0 && self.last_withdrawal_at > 0 {
+            let next_allowed = self
+                .last_withdrawal_at
+                .checked_add(self.cooldown_seconds)
+                .ok_or(AurumError::ArithmeticOverflow)?;
+            require!(clock.unix_timestamp >= next_allowed, AurumError::WithdrawalCooldown);
+        }
+
+        self.roll_epoch(clock);
+        self.withdrawn_this_epoch = self.add_capped(self.withdrawn_this_epoch, amount)?;
+        self.last_withdrawal_at = clock.unix_timestamp;
+        Ok(())
+    }
+
+    /// Record lamports paid out by `distribute_fees`. Distributions have their
+    /// own epoch budget and no cooldown, so the permissionless crank cannot
+    /// hold up admin withdrawals.
+    pub fn record_distribution(&mut self, amount: u64, clock: &Clock) -> Result<()> {
+        self.roll_epoch(clock);
+        self.distributed_this_epoch = self.add_capped(self.distributed_this_epoch, amount)?;
+        Ok(())
+    }
+
+    /// Fail unless `destination` is on the allowlist
+    pub fn check_destination(&self, destination: &Pubkey) -> Result<()> {
+        require!(
+            self.allowed_destinations.contains(destination),
+            AurumError::DestinationNotAllowed
+        );
+        Ok(())
+    }
+
+    /// Lamports `distribute_fees` may still pay out in the current epoch
+    pub fn distributable_this_epoch(&self, clock: &Clock) -> u64 {
+        if self.epoch_cap == 0 {
+            u64::MAX
+        } else if clock.epoch != self.current_epoch {
+            self.epoch_cap
+        } else {
+            self.epoch_cap.saturating_sub(self.distributed_this_epoch)
+        }
+    }
+
+    /// Start a new window when the epoch has changed
+    fn roll_epoch(&mut self, clock: &Clock) {
+        if clock.epoch != self.current_epoch {
+            self.current_epoch = clock.epoch;
+            self.withdrawn_this_epoch = 0;
+            self.distributed_this_epoch = 0;
+        }
+    }
+
+    /// `used + amount`, failing if it exceeds the epoch cap
+    fn add_capped(&self, used: u64, amount: u64) -> Result<u64> {
+        let total = used.checked_add(amount).ok_or(AurumError::ArithmeticOverflow)?;
+        if self.epoch_cap > 0 {
+            require!(total <= self.epoch_cap, AurumError::WithdrawalCapExceeded);
+        }
+        Ok(total)
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LpPoolInfo {
+    /// AMM program that owns the pool
+    pub amm_program: Pubkey,
+    /// Pool state account
+    pub pool: Pubkey,
+    /// First mint of the pair
+    pub mint_a: Pubkey,
+    /// Second mint of the pair
+    pub mint_b: Pubkey,
+}
+
+#[account]
+pub struct LockRecord {
+    /// Account layout version
+    pub version: u8,
+    /// Locker-chosen id (PDA seed)
+    pub lock_id: u64,
+    /// Wallet that locked the tokens and paid rent
+    pub locker: Pubkey,
+    /// Wallet allowed to unlock the tokens
+    pub beneficiary: Pubkey,
+    /// Locked mint
+    pub mint: Pubkey,
+    /// Amount held in escrow for this record
+    pub amount: u64,
+    /// Lock creation timestamp
+    pub created_at: i64,
+    /// Timestamp after which the tokens can be unlocked
+    pub unlock_time: i64,
+    /// AMM pool metadata when the locked mint is an LP token
+    pub pool: Option<LpPoolInfo>,
+    /// PDA bump
+    pub bump: u8,
+    /// End of linear vesting from `unlock_time` (0 = everything unlocks at `unlock_time`)
+    pub vesting_end: i64,
+    /// Amount already released to the beneficiary
+    pub released: u64,
+    /// Reserved for future fields
+    pub reserved: [u8; ACCOUNT_RESERVED_BYTES - 16],
+}
+
+impl LockRecord {
+    /// Current account layout version (2 carved `vesting_end` and `released`
+    /// out of the reserved bytes; zero values keep version 1 semantics)
+    pub const VERSION: u8 = 2;
+
+    /// Space needed for LockRecord account
+    pub const SPACE: usize = 8 + // discriminator
+        1 + // version
+        8 + // lock_id
+        32 + // locker
+        32 + // beneficiary
+        32 + // mint
+        8 + // amount
+        8 + // created_at
+        8 + // unlock_time
+        1 + 4 * 32 + // pool
+        1 + // bump
+        8 + // vesting_end
+        8 + // released
+        ACCOUNT_RESERVED_BYTES - 16; // reserved
+
+    /// Amount vested at `now`
+    pub fn vested(&self, now: i64) -> u64 {
+        if now < self.unlock_time {
+            return 0;
+        }
+        if self.vesting_end <= self.unlock_time || now >= self.vesting_end {
+            return self.amount;
+        }
+        let elapsed = (now - self.unlock_time) as u128;
+        let duration = (self.vesting_end - self.unlock_time) as u128;
+        (self.amount as u128 * elapsed / duration) as u64
+    }
+
+    /// Amount that can be released at `now`
+    pub fn releasable(&self, now: i64) -> u64 {
+        self.vested(now).saturating_sub(self.released)
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BatchLockEntry {
+    /// Wallet allowed to unlock this entry
+    pub beneficiary: Pubkey,
+    /// Amount locked for the beneficiary
+    pub amount: u64,
+    /// Cliff: nothing unlocks before this timestamp
+    pub unlock_time: i64,
+    /// Optional end of linear vesting after `unlock_time`
+    pub vesting_end: Option<i64>,
+}
+
+impl BatchLockEntry {
+    /// Serialized size of one entry in instruction data
+    pub const SERIALIZED_SIZE: usize = 32 + 8 + 8 + 1 + 8;
+}
+
+#[account]
+pub struct Distributor {
+    /// Account layout version
+    pub version: u8,
+    /// Wallet that funded the distributor and may claw back
+    pub authority: Pubkey,
+    /// Authority-chosen id (PDA seed)
+    pub distributor_id: u64,
+    /// Distributed mint
+    pub mint: Pubkey,
+    /// Token account holding the undistributed tokens
+    pub vault: Pubkey,
+    /// Root of the claim tree (see `merkle::leaf`)
+    pub merkle_root: [u8; 32],
+    /// Sum of all claim amounts in the tree
+    pub total_amount: u64,
+    /// Number of leaves in the tree
+    pub max_num_nodes: u64,
+    /// Amount claimed so far
+    pub total_claimed: u64,
+    /// Number of claims made so far
+    pub num_claims: u64,
+    /// Timestamp after which the authority may claw back unclaimed tokens
+    pub clawback_time: i64,
+    /// Whether unclaimed tokens were clawed back (claims are closed)
+    pub clawed_back: bool,
+    /// PDA bump
+    pub bump: u8,
+    /// Reserved for future fields
+    pub reserved: [u8; ACCOUNT_RESERVED_BYTES],
+}
+
+impl Distributor {
+    /// Current account layout version
+    pub const VERSION: u8 = 1;
+
+    /// Space needed for Distributor account
+    pub const SPACE: usize = 8 + // discriminator
+        1 + // version
+        32 + // authority
+        8 + // distributor_id
+        32 + // mint
+        32 + // vault
+        32 + // merkle_root
+        8 + // total_amount
+        8 + // max_num_nodes
+        8 + // total_claimed
+        8 + // num_claims
+        8 + // clawback_time
+        1 + // clawed_back
+        1 + // bump
+        ACCOUNT_RESERVED_BYTES; // reserved
+}
+
+#[account]
+pub struct ClaimStatus {
+    /// Account layout version
+    pub version: u8,
+    /// Distributor the claim belongs to
+    pub distributor: Pubkey,
+    /// Leaf index (PDA seed)
+    pub index: u64,
+    /// Wallet that claimed
+    pub claimant: Pubkey,
+    /// Amount claimed
+    pub amount: u64,
+    /// Claim timestamp
+    pub claimed_at: i64,
+    /// PDA bump
+    pub bump: u8,
+    /// Reserved for future fields
+    pub reserved: [u8; ACCOUNT_RESERVED_BYTES],
+}
+
+impl ClaimStatus {
+    /// Current account layout version
+    pub const VERSION: u8 = 1;
+
+    /// Space needed for ClaimStatus account
+    pub const SPACE: usize = 8 + // discriminator
+        1 + // version
+        32 + // distributor
+        8 + // index
+        32 + // claimant
+        8 + // amount
+        8 + // claimed_at
+        1 + // bump
+        ACCOUNT_RESERVED_BYTES; // reserved
+}
+
+#[account]
+pub struct BurnStats {
+    /// Account layout version
+    pub version: u8,
+    /// Mint the statistics are for (PDA seed)
+    pub mint: Pubkey,
+    /// Cumulative amount burned through `burn_tokens`
+    pub total_burned: u64,
+    /// Number of burns (also the sequence number of the next receipt)
+    pub burn_count: u64,
+    /// Wallet that made the most recent burn
+    pub last_burner: Pubkey,
+    /// Timestamp of the most recent burn
+    pub last_burn_at: i64,
+    /// PDA bump
+    pub bump: u8,
+    /// Reserved for future fields
+    pub reserved: [u8; ACCOUNT_RESERVED_BYTES],
+}
+
+impl BurnStats {
+    /// Current account layout version
+    pub const VERSION: u8 = 1;
+
+    /// Space needed for BurnStats account
+    pub const SPACE: usize = 8 + // discriminator
+        1 + // version
+        32 + // mint
+        8 + // total_burned
+        8 + // burn_count
+        32 + // last_burner
+        8 + // last_burn_at
+        1 + // bump
+        ACCOUNT_RESERVED_BYTES; // reserved
+
+    /// Add a burn to the running totals
+    pub fn record(&mut self, burner: Pubkey, amount: u64, now: i64) -> Result<()> {
+        self.total_burned = self
+            .total_burned
+            .checked_add(amount)
+            .ok_or(AurumError::ArithmeticOverflow)?;
+        self.burn_count = self
+            .burn_count
+            .checked_add(1)
+            .ok_or(AurumError::ArithmeticOverflow)?;
+        self.last_burner = burner;
+        self.last_burn_at = now;
+        Ok(())
+    }
+}
+
+/// Program-owned record of a single burn; it has no owner field and no
+/// instruction moves it, so it cannot be transferred
+#[account]
+pub struct BurnReceipt {
+    /// Account layout version
+    pub version: u8,
+    /// Burned mint
+    pub mint: Pubkey,
+    /// Wallet that burned the tokens
+    pub burner: Pubkey,
+    /// Amount burned
+    pub amount: u64,
+    /// Position of this burn in `BurnStats.burn_count` order (PDA seed)
+    pub sequence: u64,
+    /// Mint supply right after the burn
+    pub supply_after: u64,
+    /// `BurnStats.total_burned` right after the burn
+    pub total_burned_after: u64,
+    /// Burn timestamp
+    pub burned_at: i64,
+    /// PDA bump
+    pub bump: u8,
+    /// Reserved for future fields
+    pub reserved: [u8; ACCOUNT_RESERVED_BYTES],
+}
+
+impl BurnReceipt {
+    /// Current account layout version
+    pub const VERSION: u8 = 1;
+
+    /// Space needed for BurnReceipt account
+    pub const SPACE: usize = 8 + // discriminator
+        1 + // version
+        32 + // mint
+        32 + // burner
+        8 + // amount
+        8 + // sequence
+        8 + // supply_after
+        8 + // total_burned_after
+        8 + // burned_at
+        1 + // bump
+        ACCOUNT_RESERVED_BYTES; // reserved
+}
+
+#[account]
+pub struct BurnSchedule {
+    /// Account layout version
+    pub version: u8,
+    /// Wallet that created the schedule
+    pub authority: Pubkey,
+    /// Authority-chosen id (PDA seed)
+    pub schedule_id: u64,
+    /// Mint burned by the schedule
+    pub mint: Pubkey,
+    /// Token account holding the tokens still to be burned
+    pub vault: Pubkey,
+    /// Amount burned per elapsed interval
+    pub amount_per_interval: u64,
+    /// Interval length in seconds
+    pub interval: i64,
+    /// Timestamp at which the next interval's amount becomes due
+    pub next_burn_at: i64,
+    /// Lamports paid to the caller of each successful `crank_burn`
+    pub bounty_per_crank: u64,
+    /// Total tokens deposited into the vault
+    pub total_deposited: u64,
+    /// Total tokens burned by cranks
+    pub total_burned: u64,
+    /// Number of successful cranks
+    pub crank_count: u64,
+    /// PDA bump
+    pub bump: u8,
+    /// Reserved for future fields
+    pub reserved: [u8; ACCOUNT_RESERVED_BYTES],
+}
+
+impl BurnSchedule {
+    /// Current account layout version
+    pub const VERSION: u8 = 1;
+
+    /// Space needed for BurnSchedule account
+    pub const SPACE: usize = 8 + // discriminator
+        1 + // version
+        32 + // authority
+        8 + // schedule_id
+        32 + // mint
+        32 + // vault
+        8 + // amount_per_interval
+        8 + // interval
+        8 + // next_burn_at
+        8 + // bounty_per_crank
+        8 + // total_deposited
+        8 + // total_burned
+        8 + // crank_count
+        1 + // bump
+        ACCOUNT_RESERVED_BYTES; // reserved
+
+    /// Number of intervals due at `now`
+    pub fn intervals_due(&self, now: i64) -> u64 {
+        if now < self.next_burn_at || self.interval <= 0 {
+            return 0;
+        }
+        ((now - self.next_burn_at) / self.interval + 1) as u64
+    }
+
+    /// Pay up to `bounty_per_crank` from the schedule's lamports above rent
+    /// exemption, returning the amount paid
+    pub fn pay_bounty(schedule: &AccountInfo, to: &AccountInfo, bounty: u64) -> Result<u64> {
+        let rent_exempt = Rent::get()?.minimum_balance(schedule.data_len());
+        let amount = bounty.min(schedule.lamports().saturating_sub(rent_exempt));
+        if amount > 0 {
+            **schedule.try_borrow_mut_lamports()? -= amount;
+            **to.try_borrow_mut_lamports()? = to
+                .lamports()
+                .checked_add(amount)
+                .ok_or(AurumError::ArithmeticOverflow)?;
+        }
+        Ok(amount)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn clock(epoch: u64, unix_timestamp: i64) -> Clock {
+        Clock {
+            epoch,
+            unix_timestamp,
+            ..Clock::default()
+        }
+    }
+
+    fn policy(destination: Pubkey, epoch_cap: u64, cooldown_seconds: i64) -> TreasuryPolicy {
+        TreasuryPolicy {
+            version: TreasuryPolicy::VERSION,
+            allowed_destinations: vec![destination],
+            epoch_cap,
+            cooldown_seconds,
+            current_epoch: 0,
+            withdrawn_this_epoch: 0,
+            last_withdrawal_at: 0,
+            bump: 255,
+            distributed_this_epoch: 0,
+            reserved: [0; ACCOUNT_RESERVED_BYTES - 8],
+        }
+    }
+
+    #[test]
+    fn distribution_does_not_block_withdrawal() {
+        let owner = Pubkey::new_unique();
+        let mut policy = policy(owner, 1_000, 3_600);
+
+        // A distribution right before the withdrawal uses its own full budget
+        assert_eq!(policy.distributable_this_epoch(&clock(1, 100)), 1_000);
+        policy.record_distribution(1_000, &clock(1, 100)).unwrap();
+        assert_eq!(policy.distributable_this_epoch(&clock(1, 101)), 0);
+
+        policy
+            .record_withdrawal(&owner, 1_000, &clock(1, 101))
+            .unwrap();
+        assert_eq!(policy.withdrawn_this_epoch, 1_000);
+        assert_eq!(policy.last_withdrawal_at, 101);
+    }
+
+    #[test]
+    fn withdrawals_keep_cooldown_and_cap() {
+        let owner = Pubkey::new_unique();
+        let mut policy = policy(owner, 1_000, 3_600);
+
+        policy
+            .record_withdrawal(&owner, 600, &clock(1, 100))
+            .unwrap();
+        assert!(policy
+            .record_withdrawal(&owner, 100, &clock(1, 3_699))
+            .is_err());
+        assert!(policy
+            .record_withdrawal(&owner, 500, &clock(1, 3_700))
+            .is_err());
+        policy
+            .record_withdrawal(&owner, 400, &clock(1, 3_700))
+            .unwrap();
+        assert!(policy
+            .record_withdrawal(&Pubkey::new_unique(), 1, &clock(2, 9_000))
+            .is_err());
+
+        // A new epoch resets both budgets
+        policy.record_distribution(1_000, &clock(1, 3_701)).unwrap();
+        assert!(policy.record_distribution(1, &clock(1, 3_702)).is_err());
+        policy
+            .record_withdrawal(&owner, 1_000, &clock(2, 9_000))
+            .unwrap();
+        assert_eq!(policy.distributed_this_epoch, 0);
+        policy.record_distribution(1_000, &clock(2, 9_000)).unwrap();
+    }
+
+    #[test]
+    fn version_one_stake_counts_in_full_and_new_stake_matures() {
+        let now = 1_700_000_000;
+        // A version 1 account has zeroed `recent` fields, so all of it counts
+        let mut stake = StakeAccount {
+            version: 1,
+            owner: Pubkey::new_unique(),
+            amount: 5_000,
+            unstaking: 0,
+            unstake_available_at: 0,
+            bump: 255,
+            recent: 0,
+            recent_since: 0,
+            reserved: [0; ACCOUNT_RESERVED_BYTES - 16],
+        };
+        assert_eq!(stake.eligible_amount(now), 5_000);
+
+        stake.add_stake(1_000, now).unwrap();
+        assert_eq!(stake.eligible_amount(now), 5_000);
+        assert_eq!(stake.eligible_amount(now + MIN_STAKE_AGE), 6_000);
+
+        // Unstaking removes counted tokens first
+        stake.remove_stake(5_500).unwrap();
+        assert_eq!(stake.recent, 500);
+        assert_eq!(stake.eligible_amount(now), 0);
+    }
 }
@@ -0,0 +1,532 @@
+//! Admin CLI for the AURUM hybrid pricing program.
+//!
+//! Amounts are given and shown in SOL. With `--dry-run`, transactions are
+//! simulated instead of sent and the program logs are printed. The `offline`
+//! commands build durable-nonce transactions for keys on an air-gapped host.
+
+mod offline;
+
+use std::error::Error;
+use std::process;
+use std::str::FromStr;
+
+use anchor_lang::Discriminator;
+use aurum_client::program::state::{PricingUpdate, ReferralAccount};
+use aurum_client::{accounts, instructions, pda, ID};
+use clap::{Args, Parser, Subcommand};
+use solana_client::rpc_client::RpcClient;
+use solana_client::rpc_config::RpcProgramAccountsConfig;
+use solana_client::rpc_filter::{Memcmp, RpcFilterType};
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{read_keypair_file, Keypair, Signer};
+use solana_sdk::transaction::Transaction;
+
+pub type CliResult<T> = Result<T, Box<dyn Error>>;
+
+const LAMPORTS_PER_SOL: u64 = 1_000_000_000;
+
+#[derive(Parser)]
+#[command(
+    name = "aurum-admin",
+    about = "Manage AURUM config, pricing and treasury"
+)]
+struct Cli {
+    /// RPC endpoint
+    #[arg(
+        long,
+        short = 'u',
+        global = true,
+        default_value = "https://api.mainnet-beta.solana.com"
+    )]
+    url: String,
+
+    /// Admin keypair file
+    #[arg(
+        long,
+        short = 'k',
+        global = true,
+        default_value = "~/.config/solana/id.json"
+    )]
+    keypair: String,
+
+    /// Additional admin multisig signer keypair files
+    #[arg(long = "co-signer", global = true)]
+    co_signers: Vec<String>,
+
+    /// Simulate the transaction and print its logs instead of sending it
+    #[arg(long, global = true)]
+    dry_run: bool,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Create the config and treasury with default pricing
+    Init,
+    /// Print the current config
+    ShowConfig,
+    /// Update fees; omitted fees are unchanged
+    SetPricing(PricingArgs),
+    /// Treasury commands
+    Treasury {
+        #[command(subcommand)]
+        command: TreasuryCommand,
+    },
+    /// Withdraw fees from the treasury
+    Withdraw(WithdrawArgs),
+    /// Referral commands
+    Referral {
+        #[command(subcommand)]
+        command: ReferralCommand,
+    },
+    /// Build, sign and submit durable-nonce transactions through a file
+    Offline {
+        #[command(subcommand)]
+        command: OfflineCommand,
+    },
+}
+
+#[derive(Args)]
+struct PricingArgs {
+    #[arg(long, value_parser = parse_sol)]
+    create_fee: Option<u64>,
+    #[arg(long, value_parser = parse_sol)]
+    lock_fee: Option<u64>,
+    #[arg(long, value_parser = parse_sol)]
+    burn_fee: Option<u64>,
+    #[arg(long, value_parser = parse_sol)]
+    referral_discount: Option<u64>,
+    #[arg(long, value_parser = parse_sol)]
+    referral_rebate: Option<u64>,
+    #[arg(long, value_parser = parse_sol)]
+    premium_anchor_price: Option<u64>,
+}
+
+impl PricingArgs {
+    fn update(&self) -> CliResult<PricingUpdate> {
+        let update = PricingUpdate {
+            create_fee: self.create_fee,
+            lock_fee: self.lock_fee,
+            burn_fee: self.burn_fee,
+            referral_discount: self.referral_discount,
+            referral_rebate: self.referral_rebate,
+            premium_anchor_price: self.premium_anchor_price,
+        };
+        if update == PricingUpdate::default() {
+            return Err("no fees given".into());
+        }
+        Ok(update)
+    }
+}
+
+#[derive(Args)]
+struct WithdrawArgs {
+    /// Amount in SOL
+    #[arg(value_parser = parse_sol)]
+    amount: u64,
+    /// Allowlisted destination (defaults to the admin keypair)
+    #[arg(long)]
+    destination: Option<Pubkey>,
+}
+
+#[derive(Subcommand)]
+enum TreasuryCommand {
+    /// Print the treasury balance and accounting counters
+    Balance,
+}
+
+#[derive(Subcommand)]
+enum ReferralCommand {
+    /// Print a referral account, looked up by code or referrer wallet
+    Show { code: String },
+}
+
+#[derive(Subcommand)]
+enum OfflineCommand {
+    /// Build an unsigned transaction on a durable nonce (online, no keypair needed)
+    Build(Box<BuildArgs>),
+    /// Sign a transaction file with `--keypair` and any `--co-signer` (offline)
+    Sign { file: String },
+    /// Submit a fully signed transaction file (online)
+    Submit { file: String },
+}
+
+#[derive(Args)]
+struct BuildArgs {
+    /// Admin (config owner) public key; also pays the transaction fee
+    #[arg(long)]
+    owner: Pubkey,
+    /// Durable nonce account
+    #[arg(long)]
+    nonce: Pubkey,
+    /// Nonce authority (defaults to the owner)
+    #[arg(long)]
+    nonce_authority: Option<Pubkey>,
+    /// Additional admin multisig signer public keys
+    #[arg(long = "signer")]
+    signers: Vec<Pubkey>,
+    /// Output file
+    #[arg(long, short = 'o')]
+    out: String,
+    #[command(subcommand)]
+    action: OfflineAction,
+}
+
+#[derive(Subcommand)]
+enum OfflineAction {
+    /// `withdraw_fees`
+    Withdraw(WithdrawArgs),
+    /// `update_pricing`
+    SetPricing(PricingArgs),
+}
+
+/// Parse a SOL amount such as `0.6` into lamports without rounding
+fn parse_sol(value: &str) -> Result<u64, String> {
+    let (whole, fraction) = value.split_once('.').unwrap_or((value, ""));
+    if fraction.len() > 9 || (whole.is_empty() && fraction.is_empty()) {
+        return Err(format!("invalid SOL amount: {}", value));
+    }
+    let whole: u64 = if whole.is_empty() {
+        0
+    } else {
+        whole
+            .parse()
+            .map_err(|_| format!("invalid SOL amount: {}", value))?
+    };
+    let fraction: u64 = format!("{:0<9}", fraction)
+        .parse()
+        .map_err(|_| format!("invalid SOL amount: {}", value))?;
+    whole
+        .checked_mul(LAMPORTS_PER_SOL)
+        .and_then(|lamports| lamports.checked_add(fraction))
+        .ok_or_else(|| format!("SOL amount too large: {}", value))
+}
+
+fn sol(lamports: u64) -> String {
+    let text = format!(
+        "{}.{:09}",
+        lamports / LAMPORTS_PER_SOL,
+        lamports % LAMPORTS_PER_SOL
+    );
+    format!("{} SOL", text.trim_end_matches('0').trim_end_matches('.'))
+}
+
+fn read_keypair(path: &str) -> CliResult<Keypair> {
+    let path = match path.strip_prefix("~/") {
+        Some(rest) => format!("{}/{}", std::env::var("HOME")?, rest),
+        None => path.to_string(),
+    };
+    read_keypair_file(&path).map_err(|err| format!("reading keypair {}: {}", path, err).into())
+}
+
+struct Admin {
+    rpc: RpcClient,
+    keypair: String,
+    co_signers: Vec<String>,
+    dry_run: bool,
+}
+
+impl Admin {
+    /// The admin keypair followed by the co-signer keypairs
+    fn signers(&self) -> CliResult<Vec<Keypair>> {
+        std::iter::once(&self.keypair)
+            .chain(self.co_signers.iter())
+            .map(|path| read_keypair(path))
+            .collect()
+    }
+
+    /// Sign with the admin and co-signers, then send (or simulate)
+    fn submit(&self, signers: &[Keypair], instruction: Instruction) -> CliResult<()> {
+        let blockhash = self.rpc.get_latest_blockhash()?;
+        let tx = Transaction::new_signed_with_payer(
+            &[instruction],
+            Some(&signers[0].pubkey()),
+            &signers.iter().collect::<Vec<_>>(),
+            blockhash,
+        );
+        self.send(&tx)
+    }
+
+    /// Send (or simulate) a signed transaction
+    fn send(&self, tx: &Transaction) -> CliResult<()> {
+        if self.dry_run {
+            let result = self.rpc.simulate_transaction(tx)?.value;
+            for line in result.logs.unwrap_or_default() {
+                println!("{}", line);
+            }
+            match result.err {
+                Some(err) => return Err(format!("simulation failed: {}", err).into()),
+                None => println!(
+                    "Simulation succeeded ({} compute units)",
+                    result.units_consumed.unwrap_or(0)
+                ),
+            }
+        } else {
+            let signature = self.rpc.send_and_confirm_transaction(tx)?;
+            println!("Signature: {}", signature);
+        }
+        Ok(())
+    }
+
+    fn show_config(&self) -> CliResult<()> {
+        let (address, _) = pda::config();
+        let config = accounts::decode_config(&self.rpc.get_account_data(&address)?)?;
+
+        println!("Config: {} (version {})", address, config.version);
+        println!("Owner: {}", config.owner);
+        println!("Create fee: {}", sol(config.create_fee));
+        println!("Lock fee: {}", sol(config.lock_fee));
+        println!("Burn fee: {}", sol(config.burn_fee));
+        println!(
+            "Multisend fee: {} + {} per recipient",
+            sol(config.multisend_flat_fee),
+            sol(config.multisend_fee_per_recipient)
+        );
+        println!(
+            "Credits: {} bps discount, {} bps refund fee",
+            config.credit_discount_bps, config.credit_refund_fee_bps
+        );
+        for tier in config.stake_tiers.iter().filter(|tier| tier.discount_bps > 0) {
+            println!(
+                "Stake tier: {} tokens staked, {} bps discount",
+                tier.min_stake, tier.discount_bps
+            );
+        }
+        println!("Referral discount: {}", sol(config.referral_discount));
+        println!("Referral rebate: {}", sol(config.referral_rebate));
+        println!("Premium anchor price: {}", sol(config.premium_anchor_price));
+        if config.is_multisig() {
+            println!(
+                "Admin multisig: {} of {}",
+                config.admin_threshold,
+                config.admin_signers.len()
+            );
+            for signer in &config.admin_signers {
+                println!("  {}", signer);
+            }
+        } else {
+            println!("Admin multisig: disabled");
+        }
+        Ok(())
+    }
+
+    fn treasury_balance(&self) -> CliResult<()> {
+        let (address, _) = pda::treasury();
+        let account = self.rpc.get_account(&address)?;
+        let treasury = accounts::decode_treasury(&account.data)?;
+        let rent_exempt = self
+            .rpc
+            .get_minimum_balance_for_rent_exemption(account.data.len())?;
+
+        println!("Treasury: {} (version {})", address, treasury.version);
+        println!("Balance: {}", sol(account.lamports));
+        println!(
+            "Withdrawable: {}",
+            sol(account.lamports.saturating_sub(rent_exempt))
+        );
+        println!("Total collected: {}", sol(treasury.total_collected));
+        println!("Total rebated: {}", sol(treasury.total_rebated));
+        println!("Total withdrawn: {}", sol(treasury.total_withdrawn));
+        println!("Total distributed: {}", sol(treasury.total_distributed));
+        println!(
+            "Creates: {} ({})",
+            treasury.creates,
+            sol(treasury.create_fees)
+        );
+        println!("Locks: {} ({})", treasury.locks, sol(treasury.lock_fees));
+        println!("Burns: {} ({})", treasury.burns, sol(treasury.burn_fees));
+        println!(
+            "Multisends: {} ({})",
+            treasury.multisends,
+            sol(treasury.multisend_fees)
+        );
+        Ok(())
+    }
+
+    /// Look a referral up by referrer wallet, or scan referral accounts for the code
+    fn find_referral(&self, code: &str) -> CliResult<(Pubkey, ReferralAccount)> {
+        if let Ok(referrer) = Pubkey::from_str(code) {
+            let (address, _) = pda::referral(&referrer);
+            let referral = accounts::decode_referral(&self.rpc.get_account_data(&address)?)?;
+            return Ok((address, referral));
+        }
+
+        let config = RpcProgramAccountsConfig {
+            filters: Some(vec![RpcFilterType::Memcmp(Memcmp::new_raw_bytes(
+                0,
+                ReferralAccount::DISCRIMINATOR.to_vec(),
+            ))]),
+            ..RpcProgramAccountsConfig::default()
+        };
+        for (address, account) in self.rpc.get_program_accounts_with_config(&ID, config)? {
+            if let Ok(referral) = accounts::decode_referral(&account.data) {
+                if referral.code == code {
+                    return Ok((address, referral));
+                }
+            }
+        }
+        Err(format!("referral code {} not found", code).into())
+    }
+
+    fn show_referral(&self, code: &str) -> CliResult<()> {
+        let (address, referral) = self.find_referral(code)?;
+
+        println!("Referral: {} (version {})", address, referral.version);
+        println!("Code: {}", referral.code);
+        println!("Referrer: {}", referral.referrer);
+        println!("Active: {}", referral.is_active);
+        println!("Referrals: {}", referral.total_referrals);
+        println!("Rebate earned: {}", sol(referral.rebate_earned));
+        match referral.expires_at {
+            Some(expires_at) => println!("Expires at: {}", expires_at),
+            None => println!("Expires at: never"),
+        }
+        match referral.max_uses {
+            Some(max_uses) => println!("Max uses: {}", max_uses),
+            None => println!("Max uses: unlimited"),
+        }
+        Ok(())
+    }
+}
+
+fn run(cli: Cli) -> CliResult<()> {
+    let admin = Admin {
+        rpc: RpcClient::new_with_commitment(cli.url, CommitmentConfig::confirmed()),
+        keypair: cli.keypair,
+        co_signers: cli.co_signers,
+        dry_run: cli.dry_run,
+    };
+
+    match cli.command {
+        Command::Init => {
+            let signers = admin.signers()?;
+            admin.submit(
+                &signers,
+                instructions::initialize_config(&signers[0].pubkey()),
+            )
+        }
+        Command::ShowConfig => admin.show_config(),
+        Command::SetPricing(args) => {
+            let signers = admin.signers()?;
+            let (owner, co_signers) = split_signers(&signers);
+            let instruction = instructions::update_pricing(&owner, &args.update()?, &co_signers);
+            admin.submit(&signers, instruction)
+        }
+        Command::Treasury {
+            command: TreasuryCommand::Balance,
+        } => admin.treasury_balance(),
+        Command::Withdraw(args) => {
+            let signers = admin.signers()?;
+            let (owner, co_signers) = split_signers(&signers);
+            let instruction =
+                instructions::withdraw_fees(&owner, args.amount, args.destination, &co_signers);
+            admin.submit(&signers, instruction)
+        }
+        Command::Referral {
+            command: ReferralCommand::Show { code },
+        } => admin.show_referral(&code),
+        Command::Offline {
+            command: OfflineCommand::Build(args),
+        } => {
+            let BuildArgs {
+                owner,
+                nonce,
+                nonce_authority,
+                signers,
+                out,
+                action,
+            } = *args;
+            let (instruction, description) = match action {
+                OfflineAction::Withdraw(args) => (
+                    instructions::withdraw_fees(&owner, args.amount, args.destination, &signers),
+                    format!(
+                        "withdraw_fees {} to {}",
+                        sol(args.amount),
+                        args.destination.unwrap_or(owner)
+                    ),
+                ),
+                OfflineAction::SetPricing(args) => {
+                    let update = args.update()?;
+                    (
+                        instructions::update_pricing(&owner, &update, &signers),
+                        format!("update_pricing {:?}", update),
+                    )
+                }
+            };
+            let file = offline::build(
+                &admin.rpc,
+                instruction,
+                description,
+                &owner,
+                &nonce,
+                &nonce_authority.unwrap_or(owner),
+            )?;
+            file.write(&out)?;
+            println!(
+                "Wrote {}; sign it with `aurum-admin offline sign {}`",
+                out, out
+            );
+            Ok(())
+        }
+        Command::Offline {
+            command: OfflineCommand::Sign { file: path },
+        } => {
+            let mut file = offline::OfflineTransaction::read(&path)?;
+            println!("Signing:");
+            for line in offline::describe(&file.transaction()?)? {
+                println!("  {}", line);
+            }
+            let missing = file.sign(&admin.signers()?)?;
+            file.write(&path)?;
+            if missing.is_empty() {
+                println!(
+                    "Fully signed; submit it with `aurum-admin offline submit {}`",
+                    path
+                );
+            } else {
+                for signer in missing {
+                    println!("Still needs a signature from {}", signer);
+                }
+            }
+            Ok(())
+        }
+        Command::Offline {
+            command: OfflineCommand::Submit { file: path },
+        } => {
+            let file = offline::OfflineTransaction::read(&path)?;
+            let tx = file.transaction()?;
+            let missing = offline::missing_signers(&tx);
+            if !missing.is_empty() {
+                return Err(format!(
+                    "{} signature(s) missing, first {}",
+                    missing.len(),
+                    missing[0]
+                )
+                .into());
+            }
+            println!("Submitting:");
+            for line in offline::describe(&tx)? {
+                println!("  {}", line);
+            }
+            admin.send(&tx)
+        }
+    }
+}
+
+/// The admin key and the co-signer keys
+fn split_signers(signers: &[Keypair]) -> (Pubkey, Vec<Pubkey>) {
+    let owner = signers[0].pubkey();
+    let co_signers = signers[1..].iter().map(|signer| signer.pubkey()).collect();
+    (owner, co_signers)
+}
+
+fn main() {
+    if let Err(err) = run(Cli::parse()) {
+        eprintln!("error: {}", err);
+        process::exit(1);
+    }
+}
@@ -0,0 +1,267 @@
+//! Durable-nonce transactions for admin keys kept on an air-gapped machine.
+//!
+//! `build` runs online and needs only public keys, `sign` runs offline and
+//! needs no RPC, and `submit` sends the signed file. The nonce stands in for
+//! the recent blockhash, so the file stays valid until the nonce is advanced.
+
+use std::fs;
+
+use anchor_lang::{AnchorDeserialize, Discriminator};
+use aurum_client::program::instruction::{UpdatePricing, WithdrawFees};
+use aurum_client::{pda, ID};
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use solana_client::nonce_utils;
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::instruction::{CompiledInstruction, Instruction};
+use solana_sdk::message::Message;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signature};
+use solana_sdk::system_instruction::SystemInstruction;
+use solana_sdk::system_program;
+use solana_sdk::transaction::Transaction;
+
+use crate::{sol, CliResult};
+
+/// `withdraw_fees` accounts in on-chain order; co-signers follow
+const WITHDRAW_FEES_ACCOUNTS: [&str; 6] = [
+    "config",
+    "treasury",
+    "policy",
+    "destination",
+    "owner",
+    "system_program",
+];
+
+/// `update_pricing` accounts in on-chain order; co-signers follow
+const UPDATE_PRICING_ACCOUNTS: [&str; 2] = ["config", "owner"];
+
+/// Transaction file passed between the online and offline hosts
+#[derive(Serialize, Deserialize)]
+pub struct OfflineTransaction {
+    /// Summary written by `build`; informational only, `sign` and `submit`
+    /// show the decoded instructions instead
+    pub description: String,
+    pub nonce_account: String,
+    pub nonce: String,
+    /// Required signers, fee payer first
+    pub signers: Vec<String>,
+    /// Base64 of the bincode-serialized transaction
+    pub transaction: String,
+}
+
+impl OfflineTransaction {
+    pub fn read(path: &str) -> CliResult<Self> {
+        let data = fs::read_to_string(path).map_err(|e| format!("{}: {}", path, e))?;
+        Ok(serde_json::from_str(&data)?)
+    }
+
+    pub fn write(&self, path: &str) -> CliResult<()> {
+        fs::write(path, serde_json::to_string_pretty(self)? + "\n")
+            .map_err(|e| format!("{}: {}", path, e))?;
+        Ok(())
+    }
+
+    /// Decode the transaction, refusing anything but a nonce advance followed
+    /// by `withdraw_fees` or `update_pricing`
+    pub fn transaction(&self) -> CliResult<Transaction> {
+        let bytes = STANDARD.decode(&self.transaction)?;
+        let tx: Transaction = bincode::deserialize(&bytes)?;
+        describe(&tx)?;
+        Ok(tx)
+    }
+
+    fn set_transaction(&mut self, tx: &Transaction) -> CliResult<()> {
+        self.transaction = STANDARD.encode(bincode::serialize(tx)?);
+        Ok(())
+    }
+
+    /// Add signatures from `signers` and return the signers still missing
+    pub fn sign(&mut self, signers: &[Keypair]) -> CliResult<Vec<Pubkey>> {
+        let mut tx = self.transaction()?;
+        let blockhash = tx.message.recent_blockhash;
+        tx.try_partial_sign(&signers.iter().collect::<Vec<_>>(), blockhash)?;
+        self.set_transaction(&tx)?;
+        Ok(missing_signers(&tx))
+    }
+}
+
+/// Build an unsigned `instruction` on the current value of `nonce_account`,
+/// with `owner` paying the fee
+pub fn build(
+    rpc: &RpcClient,
+    instruction: Instruction,
+    description: String,
+    owner: &Pubkey,
+    nonce_account: &Pubkey,
+    nonce_authority: &Pubkey,
+) -> CliResult<OfflineTransaction> {
+    let account = nonce_utils::get_account_with_commitment(
+        rpc,
+        nonce_account,
+        CommitmentConfig::confirmed(),
+    )?;
+    let data = nonce_utils::data_from_account(&account)?;
+    if data.authority != *nonce_authority {
+        return Err(format!(
+            "nonce authority is {}, not {}",
+            data.authority, nonce_authority
+        )
+        .into());
+    }
+    let nonce = data.blockhash();
+
+    let mut message = Message::new_with_nonce(
+        vec![instruction],
+        Some(owner),
+        nonce_account,
+        nonce_authority,
+    );
+    message.recent_blockhash = nonce;
+    let tx = Transaction::new_unsigned(message);
+
+    let mut file = OfflineTransaction {
+        description,
+        nonce_account: nonce_account.to_string(),
+        nonce: nonce.to_string(),
+        signers: tx
+            .message
+            .signer_keys()
+            .iter()
+            .map(|key| key.to_string())
+            .collect(),
+        transaction: String::new(),
+    };
+    file.set_transaction(&tx)?;
+    Ok(file)
+}
+
+/// Required signers that have not signed yet
+pub fn missing_signers(tx: &Transaction) -> Vec<Pubkey> {
+    tx.message
+        .signer_keys()
+        .into_iter()
+        .zip(&tx.signatures)
+        .filter(|(_, signature)| **signature == Signature::default())
+        .map(|(key, _)| *key)
+        .collect()
+}
+
+/// Decode every instruction of `tx` into readable lines, from the
+/// transaction bytes rather than the file's `description`. Fails unless
+/// instruction 0 is the only `AdvanceNonceAccount` and every other instruction
+/// is this program's `withdraw_fees` or `update_pricing`.
+pub fn describe(tx: &Transaction) -> CliResult<Vec<String>> {
+    let message = &tx.message;
+    let (nonce, rest) = message
+        .instructions
+        .split_first()
+        .ok_or("transaction has no instructions")?;
+    if rest.is_empty() {
+        return Err("transaction has no program instruction".into());
+    }
+
+    let nonce_accounts = instruction_accounts(tx, nonce)?;
+    if program_id(tx, nonce)? != system_program::ID
+        || !matches!(
+            bincode::deserialize(&nonce.data),
+            Ok(SystemInstruction::AdvanceNonceAccount)
+        )
+        || nonce_accounts.len() != 3
+    {
+        return Err("instruction 0 is not a durable nonce advance".into());
+    }
+    let mut lines = vec![format!(
+        "advance_nonce_account nonce={} authority={}",
+        nonce_accounts[0], nonce_accounts[2]
+    )];
+
+    for instruction in rest {
+        let program = program_id(tx, instruction)?;
+        if program != ID {
+            return Err(format!("unexpected program {} in transaction", program).into());
+        }
+        let accounts = instruction_accounts(tx, instruction)?;
+        let data = &instruction.data;
+        if data.len() < 8 {
+            return Err("instruction data too short".into());
+        }
+        let (discriminator, args) = data.split_at(8);
+
+        let names: &[&str] = if discriminator == WithdrawFees::DISCRIMINATOR {
+            let args = WithdrawFees::try_from_slice(args)?;
+            lines.push(format!("withdraw_fees amount={}", sol(args.amount)));
+            &WITHDRAW_FEES_ACCOUNTS
+        } else if discriminator == UpdatePricing::DISCRIMINATOR {
+            let args = UpdatePricing::try_from_slice(args)?;
+            let fees = [
+                ("create_fee", args.create_fee),
+                ("lock_fee", args.lock_fee),
+                ("burn_fee", args.burn_fee),
+                ("referral_discount", args.referral_discount),
+                ("referral_rebate", args.referral_rebate),
+                ("premium_anchor_price", args.premium_anchor_price),
+            ];
+            let changes: Vec<String> = fees
+                .iter()
+                .filter_map(|(name, fee)| fee.map(|fee| format!("{}={}", name, sol(fee))))
+                .collect();
+            lines.push(format!("update_pricing {}", changes.join(" ")));
+            &UPDATE_PRICING_ACCOUNTS
+        } else {
+            return Err("only withdraw_fees and update_pricing can be signed offline".into());
+        };
+
+        if accounts.len() < names.len() {
+            return Err("instruction is missing accounts".into());
+        }
+        let expected = [
+            ("config", pda::config().0),
+            ("treasury", pda::treasury().0),
+            ("policy", pda::treasury_policy().0),
+        ];
+        for (name, key) in names.iter().zip(&accounts) {
+            if let Some((_, pda)) = expected.iter().find(|(expected, _)| expected == name) {
+                if pda != key {
+                    return Err(format!("{} is {}, expected {}", name, key, pda).into());
+                }
+            }
+            if *name == "destination" && *key == ID {
+                lines.push("  destination: none (owner)".to_string());
+            } else {
+                lines.push(format!("  {}: {}", name, key));
+            }
+        }
+        for co_signer in &accounts[names.len()..] {
+            lines.push(format!("  co-signer: {}", co_signer));
+        }
+    }
+    Ok(lines)
+}
+
+fn program_id(tx: &Transaction, instruction: &CompiledInstruction) -> CliResult<Pubkey> {
+    tx.message
+        .account_keys
+        .get(instruction.program_id_index as usize)
+        .copied()
+        .ok_or_else(|| "invalid program index".into())
+}
+
+fn instruction_accounts(
+    tx: &Transaction,
+    instruction: &CompiledInstruction,
+) -> CliResult<Vec<Pubkey>> {
+    instruction
+        .accounts
+        .iter()
+        .map(|index| {
+            tx.message
+                .account_keys
+                .get(*index as usize)
+                .copied()
+                .ok_or_else(|| "invalid account index".into())
+        })
+        .collect()
+}
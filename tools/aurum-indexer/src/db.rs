@@ -0,0 +1,337 @@
+//! SQLite storage. Every transaction seen is recorded in `transactions` and
+//! every event in `events`; the events dashboards query also get a typed
+//! table keyed by `(signature, event_index)`.
+//!
+//! Amounts are stored as INTEGER. A `u64` above `i64::MAX` (only possible
+//! for token amounts) does not fit: SQLite would turn it into a lossy REAL,
+//! even when bound as TEXT, so the insert fails and is rolled back instead.
+
+use aurum_client::events::AurumEvent;
+use rusqlite::{params, Connection, OptionalExtension, Result};
+
+use crate::IndexedTransaction;
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS transactions (
+    signature TEXT PRIMARY KEY,
+    slot INTEGER NOT NULL,
+    block_time INTEGER,
+    failed INTEGER NOT NULL
+);
+CREATE INDEX IF NOT EXISTS transactions_slot ON transactions (slot);
+
+CREATE TABLE IF NOT EXISTS events (
+    signature TEXT NOT NULL REFERENCES transactions (signature),
+    event_index INTEGER NOT NULL,
+    name TEXT NOT NULL,
+    PRIMARY KEY (signature, event_index)
+);
+
+CREATE TABLE IF NOT EXISTS fees_collected (
+    signature TEXT NOT NULL,
+    event_index INTEGER NOT NULL,
+    payer TEXT NOT NULL,
+    amount INTEGER NOT NULL,
+    fee_type TEXT NOT NULL,
+    exemption_discount INTEGER,
+    paid_with_credit INTEGER NOT NULL,
+    timestamp INTEGER NOT NULL,
+    PRIMARY KEY (signature, event_index)
+);
+
+CREATE TABLE IF NOT EXISTS referrals_used (
+    signature TEXT NOT NULL,
+    event_index INTEGER NOT NULL,
+    referrer TEXT NOT NULL,
+    referee TEXT NOT NULL,
+    discount_amount INTEGER NOT NULL,
+    rebate_amount INTEGER NOT NULL,
+    timestamp INTEGER NOT NULL,
+    PRIMARY KEY (signature, event_index)
+);
+
+CREATE TABLE IF NOT EXISTS stake_discounts (
+    signature TEXT NOT NULL,
+    event_index INTEGER NOT NULL,
+    payer TEXT NOT NULL,
+    stake_account TEXT NOT NULL,
+    staked INTEGER NOT NULL,
+    discount_bps INTEGER NOT NULL,
+    discount_amount INTEGER NOT NULL,
+    timestamp INTEGER NOT NULL,
+    PRIMARY KEY (signature, event_index)
+);
+
+CREATE TABLE IF NOT EXISTS tokens_created (
+    signature TEXT NOT NULL,
+    event_index INTEGER NOT NULL,
+    mint TEXT NOT NULL,
+    creator TEXT NOT NULL,
+    fee_paid INTEGER NOT NULL,
+    timestamp INTEGER NOT NULL,
+    PRIMARY KEY (signature, event_index)
+);
+
+CREATE TABLE IF NOT EXISTS tokens_locked (
+    signature TEXT NOT NULL,
+    event_index INTEGER NOT NULL,
+    mint TEXT NOT NULL,
+    locker TEXT NOT NULL,
+    amount INTEGER NOT NULL,
+    unlock_time INTEGER NOT NULL,
+    fee_paid INTEGER NOT NULL,
+    timestamp INTEGER NOT NULL,
+    PRIMARY KEY (signature, event_index)
+);
+CREATE INDEX IF NOT EXISTS tokens_locked_mint ON tokens_locked (mint);
+
+CREATE TABLE IF NOT EXISTS tokens_burned (
+    signature TEXT NOT NULL,
+    event_index INTEGER NOT NULL,
+    mint TEXT NOT NULL,
+    burner TEXT NOT NULL,
+    amount INTEGER NOT NULL,
+    fee_paid INTEGER NOT NULL,
+    timestamp INTEGER NOT NULL,
+    PRIMARY KEY (signature, event_index)
+);
+CREATE INDEX IF NOT EXISTS tokens_burned_mint ON tokens_burned (mint);
+
+CREATE TABLE IF NOT EXISTS fees_withdrawn (
+    signature TEXT NOT NULL,
+    event_index INTEGER NOT NULL,
+    recipient TEXT NOT NULL,
+    amount INTEGER NOT NULL,
+    timestamp INTEGER NOT NULL,
+    PRIMARY KEY (signature, event_index)
+);
+";
+
+pub struct Database {
+    conn: Connection,
+}
+
+impl Database {
+    /// Open (or create) the database and apply the schema
+    pub fn open(path: &str) -> Result<Self> {
+        let conn = Connection::open(path)?;
+        conn.execute_batch(SCHEMA)?;
+        Ok(Self { conn })
+    }
+
+    pub fn contains(&self, signature: &str) -> Result<bool> {
+        self.conn
+            .query_row(
+                "SELECT 1 FROM transactions WHERE signature = ?1",
+                [signature],
+                |_| Ok(()),
+            )
+            .optional()
+            .map(|row| row.is_some())
+    }
+
+    /// Signature of the newest indexed transaction
+    pub fn latest_signature(&self) -> Result<Option<String>> {
+        self.conn
+            .query_row(
+                "SELECT signature FROM transactions ORDER BY slot DESC LIMIT 1",
+                [],
+                |row| row.get(0),
+            )
+            .optional()
+    }
+
+    /// Store a transaction and its events; returns the number of events
+    /// written, or `None` if the transaction was already indexed
+    pub fn insert(
+        &mut self,
+        tx: &IndexedTransaction,
+        events: &[AurumEvent],
+    ) -> Result<Option<usize>> {
+        let db = self.conn.transaction()?;
+        let inserted = db.execute(
+            "INSERT OR IGNORE INTO transactions (signature, slot, block_time, failed)
+             VALUES (?1, ?2, ?3, ?4)",
+            params![tx.signature, int(tx.slot)?, tx.block_time, tx.failed],
+        )?;
+        if inserted == 0 {
+            return Ok(None);
+        }
+
+        for (index, event) in events.iter().enumerate() {
+            let sig = &tx.signature;
+            db.execute(
+                "INSERT INTO events (signature, event_index, name) VALUES (?1, ?2, ?3)",
+                params![sig, index, event.name()],
+            )?;
+            match event {
+                AurumEvent::FeeCollected(e) => db.execute(
+                    "INSERT INTO fees_collected VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+                    params![
+                        sig,
+                        index,
+                        e.payer.to_string(),
+                        int(e.amount)?,
+                        e.fee_type.name(),
+                        e.exemption_discount.map(int).transpose()?,
+                        e.paid_with_credit,
+                        e.timestamp
+                    ],
+                )?,
+                AurumEvent::ReferralUsed(e) => db.execute(
+                    "INSERT INTO referrals_used VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                    params![
+                        sig,
+                        index,
+                        e.referrer.to_string(),
+                        e.referee.to_string(),
+                        int(e.discount_amount)?,
+                        int(e.rebate_amount)?,
+                        e.timestamp
+                    ],
+                )?,
+                AurumEvent::StakeDiscountApplied(e) => db.execute(
+                    "INSERT INTO stake_discounts VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+                    params![
+                        sig,
+                        index,
+                        e.payer.to_string(),
+                        e.stake_account.to_string(),
+                        int(e.staked)?,
+                        e.discount_bps,
+                        int(e.discount_amount)?,
+                        e.timestamp
+                    ],
+                )?,
+                AurumEvent::TokenCreated(e) => db.execute(
+                    "INSERT INTO tokens_created VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                    params![
+                        sig,
+                        index,
+                        e.mint.to_string(),
+                        e.creator.to_string(),
+                        int(e.fee_paid)?,
+                        e.timestamp
+                    ],
+                )?,
+                AurumEvent::TokensLocked(e) => db.execute(
+                    "INSERT INTO tokens_locked VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+                    params![
+                        sig,
+                        index,
+                        e.mint.to_string(),
+                        e.locker.to_string(),
+                        int(e.amount)?,
+                        e.unlock_time,
+                        int(e.fee_paid)?,
+                        e.timestamp
+                    ],
+                )?,
+                AurumEvent::TokensBurned(e) => db.execute(
+                    "INSERT INTO tokens_burned VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                    params![
+                        sig,
+                        index,
+                        e.mint.to_string(),
+                        e.burner.to_string(),
+                        int(e.amount)?,
+                        int(e.fee_paid)?,
+                        e.timestamp
+                    ],
+                )?,
+                AurumEvent::FeesWithdrawn(e) => db.execute(
+                    "INSERT INTO fees_withdrawn VALUES (?1, ?2, ?3, ?4, ?5)",
+                    params![
+                        sig,
+                        index,
+                        e.recipient.to_string(),
+                        int(e.amount)?,
+                        e.timestamp
+                    ],
+                )?,
+                _ => 0,
+            };
+        }
+        db.commit()?;
+        Ok(Some(events.len()))
+    }
+}
+
+/// An amount as INTEGER; fails for values above `i64::MAX`
+fn int(value: u64) -> Result<i64> {
+    i64::try_from(value).map_err(|err| rusqlite::Error::ToSqlConversionFailure(Box::new(err)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aurum_client::program::events::{FeeCollected, TokensBurned};
+    use aurum_client::program::state::OperationKind;
+    use solana_sdk::pubkey::Pubkey;
+
+    fn transaction(signature: &str) -> IndexedTransaction {
+        IndexedTransaction {
+            signature: signature.to_string(),
+            slot: 42,
+            block_time: Some(1_700_000_000),
+            failed: false,
+            logs: Vec::new(),
+        }
+    }
+
+    fn count(db: &Database, table: &str) -> i64 {
+        db.conn
+            .query_row(&format!("SELECT COUNT(*) FROM {table}"), [], |row| row.get(0))
+            .unwrap()
+    }
+
+    #[test]
+    fn insert_is_idempotent() {
+        let mut db = Database::open(":memory:").unwrap();
+        let tx = transaction("sig1");
+        let events = [AurumEvent::FeeCollected(FeeCollected {
+            payer: Pubkey::new_unique(),
+            amount: 100_000_000,
+            fee_type: OperationKind::Create,
+            exemption_discount: None,
+            paid_with_credit: false,
+            timestamp: 1_700_000_000,
+        })];
+
+        assert!(!db.contains("sig1").unwrap());
+        assert_eq!(db.insert(&tx, &events).unwrap(), Some(1));
+        assert!(db.contains("sig1").unwrap());
+        assert_eq!(db.insert(&tx, &events).unwrap(), None);
+
+        assert_eq!(count(&db, "transactions"), 1);
+        assert_eq!(count(&db, "events"), 1);
+        assert_eq!(count(&db, "fees_collected"), 1);
+        assert_eq!(db.latest_signature().unwrap().as_deref(), Some("sig1"));
+    }
+
+    #[test]
+    fn amounts_above_i64_max_fail_without_partial_writes() {
+        let mut db = Database::open(":memory:").unwrap();
+        let burned = |amount| {
+            [AurumEvent::TokensBurned(TokensBurned {
+                mint: Pubkey::new_unique(),
+                burner: Pubkey::new_unique(),
+                amount,
+                fee_paid: 1,
+                timestamp: 1_700_000_000,
+            })]
+        };
+
+        assert!(db.insert(&transaction("sig2"), &burned(u64::MAX)).is_err());
+        assert!(!db.contains("sig2").unwrap());
+        assert_eq!(count(&db, "events"), 0);
+
+        let largest = i64::MAX as u64;
+        assert_eq!(db.insert(&transaction("sig2"), &burned(largest)).unwrap(), Some(1));
+        let amount: i64 = db
+            .conn
+            .query_row("SELECT amount FROM tokens_burned", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(amount as u64, largest);
+    }
+}
@@ -0,0 +1,219 @@
+//! Indexes AURUM program events into SQLite.
+//!
+//! `files` reads `getTransaction` responses saved as JSON (`json` encoding;
+//! the full RPC response, its `result`, or an array of either). `rpc` walks
+//! the program's signatures on a node, a local validator by default, and
+//! fetches every transaction newer than the last one indexed. Runs are
+//! idempotent, so either command can be repeated.
+
+mod db;
+
+use std::error::Error;
+use std::fs;
+use std::process;
+use std::str::FromStr;
+
+use aurum_client::events::parse_logs;
+use aurum_client::ID;
+use clap::{Parser, Subcommand};
+use serde_json::{json, Value};
+use solana_client::rpc_client::{GetConfirmedSignaturesForAddress2Config, RpcClient};
+use solana_client::rpc_request::RpcRequest;
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::signature::Signature;
+
+use db::Database;
+
+type CliResult<T> = Result<T, Box<dyn Error>>;
+
+/// Largest page `getSignaturesForAddress` returns
+const SIGNATURE_PAGE: usize = 1000;
+
+#[derive(Parser)]
+#[command(
+    name = "aurum-indexer",
+    about = "Index AURUM program events into SQLite"
+)]
+struct Cli {
+    /// SQLite database file, created if missing
+    #[arg(long, default_value = "aurum-events.db")]
+    db: String,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Index transactions from `getTransaction` JSON files
+    Files {
+        #[arg(required = true)]
+        files: Vec<String>,
+    },
+    /// Index the program's transactions from an RPC node
+    Rpc {
+        /// RPC endpoint
+        #[arg(long, short = 'u', default_value = "http://127.0.0.1:8899")]
+        url: String,
+    },
+}
+
+/// The parts of a transaction the indexer stores
+pub struct IndexedTransaction {
+    pub signature: String,
+    pub slot: u64,
+    pub block_time: Option<i64>,
+    pub failed: bool,
+    pub logs: Vec<String>,
+}
+
+impl IndexedTransaction {
+    /// Read a `getTransaction` result (or full response) in `json` encoding
+    fn from_json(value: &Value) -> CliResult<Self> {
+        let result = value.get("result").unwrap_or(value);
+        if result.is_null() {
+            return Err("transaction not found".into());
+        }
+        let signature = result["transaction"]["signatures"][0]
+            .as_str()
+            .ok_or("missing transaction.signatures; fetch with \"encoding\": \"json\"")?
+            .to_string();
+        let slot = result["slot"].as_u64().ok_or("missing slot")?;
+        let meta = &result["meta"];
+        let logs = meta["logMessages"]
+            .as_array()
+            .map(|logs| {
+                logs.iter()
+                    .filter_map(|line| line.as_str().map(str::to_string))
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        Ok(Self {
+            signature,
+            slot,
+            block_time: result["blockTime"].as_i64(),
+            failed: !meta["err"].is_null(),
+            logs,
+        })
+    }
+}
+
+struct Indexer {
+    db: Database,
+    transactions: usize,
+    events: usize,
+}
+
+impl Indexer {
+    fn index(&mut self, value: &Value) -> CliResult<()> {
+        let tx = IndexedTransaction::from_json(value)?;
+        // Failed transactions are recorded but their events never happened
+        let events = if tx.failed {
+            Vec::new()
+        } else {
+            parse_logs(&tx.logs)
+        };
+        if let Some(count) = self.db.insert(&tx, &events)? {
+            self.transactions += 1;
+            self.events += count;
+        }
+        Ok(())
+    }
+
+    fn index_file(&mut self, path: &str) -> CliResult<()> {
+        let data = fs::read_to_string(path).map_err(|e| format!("{}: {}", path, e))?;
+        let value: Value = serde_json::from_str(&data).map_err(|e| format!("{}: {}", path, e))?;
+        match value.as_array() {
+            Some(transactions) => transactions.iter().try_for_each(|tx| self.index(tx)),
+            None => self.index(&value),
+        }
+        .map_err(|e| format!("{}: {}", path, e).into())
+    }
+
+    fn index_rpc(&mut self, rpc: &RpcClient) -> CliResult<()> {
+        let until = self
+            .db
+            .latest_signature()?
+            .map(|signature| Signature::from_str(&signature))
+            .transpose()?;
+
+        // Pages come newest first; collect them all, then index oldest first
+        // so an interrupted run leaves no gap behind the latest signature
+        let mut signatures = Vec::new();
+        let mut before = None;
+        loop {
+            let page = rpc.get_signatures_for_address_with_config(
+                &ID,
+                GetConfirmedSignaturesForAddress2Config {
+                    before,
+                    until,
+                    limit: Some(SIGNATURE_PAGE),
+                    commitment: Some(CommitmentConfig::confirmed()),
+                },
+            )?;
+            let Some(last) = page.last() else {
+                break;
+            };
+            before = Some(Signature::from_str(&last.signature)?);
+            let full = page.len() == SIGNATURE_PAGE;
+            signatures.extend(page.into_iter().map(|status| status.signature));
+            if !full {
+                break;
+            }
+        }
+
+        for signature in signatures.iter().rev() {
+            if self.db.contains(signature)? {
+                continue;
+            }
+            let value: Value = rpc.send(
+                RpcRequest::GetTransaction,
+                json!([
+                    signature,
+                    {
+                        "encoding": "json",
+                        "commitment": "confirmed",
+                        "maxSupportedTransactionVersion": 0
+                    }
+                ]),
+            )?;
+            self.index(&value)
+                .map_err(|e| format!("{}: {}", signature, e))?;
+        }
+        Ok(())
+    }
+}
+
+fn run(cli: Cli) -> CliResult<()> {
+    let mut indexer = Indexer {
+        db: Database::open(&cli.db)?,
+        transactions: 0,
+        events: 0,
+    };
+
+    match cli.command {
+        Command::Files { files } => {
+            for path in &files {
+                indexer.index_file(path)?;
+            }
+        }
+        Command::Rpc { url } => {
+            let rpc = RpcClient::new_with_commitment(url, CommitmentConfig::confirmed());
+            indexer.index_rpc(&rpc)?;
+        }
+    }
+
+    println!(
+        "Indexed {} events from {} transactions into {}",
+        indexer.events, indexer.transactions, cli.db
+    );
+    Ok(())
+}
+
+fn main() {
+    if let Err(err) = run(Cli::parse()) {
+        eprintln!("error: {}", err);
+        process::exit(1);
+    }
+}
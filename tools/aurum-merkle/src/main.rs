@@ -0,0 +1,172 @@
+//! Build a Merkle distributor tree from a `wallet,amount` CSV.
+//!
+//! Usage: `aurum-merkle <claims.csv>`
+//!
+//! Claim indexes follow CSV row order, so the same file always yields the same
+//! root. Prints the root, totals and every claim's proof as JSON, ready for
+//! `create_distributor` and `claim`.
+
+use std::collections::HashSet;
+use std::env;
+use std::error::Error;
+use std::process;
+use std::str::FromStr;
+
+use anchor_lang::prelude::Pubkey;
+use aurum_hybrid_pricing::merkle::MerkleTree;
+use serde_json::{json, Value};
+
+fn hex(bytes: &[u8; 32]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn read_claims(path: &str) -> Result<Vec<(Pubkey, u64)>, Box<dyn Error>> {
+    let mut reader = csv::ReaderBuilder::new().trim(csv::Trim::All).from_path(path)?;
+    let mut seen = HashSet::new();
+    let mut claims = Vec::new();
+
+    for (row, record) in reader.records().enumerate() {
+        let record = record?;
+        let line = row + 2;
+        let wallet = record.get(0).ok_or(format!("line {}: missing wallet", line))?;
+        let amount = record.get(1).ok_or(format!("line {}: missing amount", line))?;
+
+        let wallet = Pubkey::from_str(wallet).map_err(|e| format!("line {}: {}", line, e))?;
+        let amount: u64 = amount.parse().map_err(|e| format!("line {}: {}", line, e))?;
+        if amount == 0 {
+            return Err(format!("line {}: zero amount", line).into());
+        }
+        if !seen.insert(wallet) {
+            return Err(format!("line {}: duplicate wallet {}", line, wallet).into());
+        }
+        claims.push((wallet, amount));
+    }
+
+    if claims.is_empty() {
+        return Err("no claims in input".into());
+    }
+    Ok(claims)
+}
+
+fn distribution(claims: &[(Pubkey, u64)]) -> Result<Value, Box<dyn Error>> {
+    let tree = MerkleTree::new(claims);
+
+    let mut total_amount: u64 = 0;
+    let mut entries = Vec::with_capacity(claims.len());
+    for (index, (wallet, amount)) in claims.iter().enumerate() {
+        total_amount = total_amount.checked_add(*amount).ok_or("total amount overflows u64")?;
+        entries.push(json!({
+            "index": index,
+            "wallet": wallet.to_string(),
+            "amount": amount,
+            "proof": tree.proof(index).iter().map(hex).collect::<Vec<_>>(),
+        }));
+    }
+
+    Ok(json!({
+        "merkle_root": hex(&tree.root()),
+        "total_amount": total_amount,
+        "max_num_nodes": claims.len(),
+        "claims": entries,
+    }))
+}
+
+fn run(path: &str) -> Result<(), Box<dyn Error>> {
+    let output = distribution(&read_claims(path)?)?;
+    println!("{}", serde_json::to_string_pretty(&output)?);
+    Ok(())
+}
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    if args.len() != 2 {
+        eprintln!("usage: {} <claims.csv>", args[0]);
+        process::exit(2);
+    }
+    if let Err(err) = run(&args[1]) {
+        eprintln!("error: {}", err);
+        process::exit(1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aurum_hybrid_pricing::merkle::{leaf, verify};
+    use std::io::Write;
+
+    fn unhex(s: &str) -> [u8; 32] {
+        let mut out = [0u8; 32];
+        for (i, byte) in out.iter_mut().enumerate() {
+            *byte = u8::from_str_radix(&s[2 * i..2 * i + 2], 16).unwrap();
+        }
+        out
+    }
+
+    fn csv(name: &str, body: &str) -> String {
+        let path = env::temp_dir().join(format!("aurum-merkle-{}-{}.csv", process::id(), name));
+        std::fs::File::create(&path).unwrap().write_all(body.as_bytes()).unwrap();
+        path.to_string_lossy().into_owned()
+    }
+
+    fn wallet(i: u8) -> Pubkey {
+        Pubkey::new_from_array([i; 32])
+    }
+
+    #[test]
+    fn output_matches_fixed_root() {
+        let claims: Vec<_> = (1..=5).map(|i| (wallet(i), i as u64 * 1_000)).collect();
+        let output = distribution(&claims).unwrap();
+        assert_eq!(
+            output["merkle_root"],
+            "384cc1baa9308b3940a6a77e26617f649e31736df53136ae4824dda34e93affa"
+        );
+        assert_eq!(output["total_amount"], 15_000);
+        assert_eq!(output["max_num_nodes"], 5);
+    }
+
+    #[test]
+    fn printed_proofs_verify_on_chain() {
+        let claims: Vec<_> = (1..=7).map(|i| (wallet(i), i as u64)).collect();
+        let output = distribution(&claims).unwrap();
+        let root = unhex(output["merkle_root"].as_str().unwrap());
+
+        for entry in output["claims"].as_array().unwrap() {
+            let index = entry["index"].as_u64().unwrap();
+            let wallet = Pubkey::from_str(entry["wallet"].as_str().unwrap()).unwrap();
+            let amount = entry["amount"].as_u64().unwrap();
+            let proof: Vec<[u8; 32]> = entry["proof"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|node| unhex(node.as_str().unwrap()))
+                .collect();
+            assert!(verify(&proof, &root, leaf(index, &wallet, amount)));
+        }
+    }
+
+    #[test]
+    fn reads_claims_in_row_order() {
+        let path = csv(
+            "ok",
+            &format!("wallet,amount\n{}, 10\n{},20\n", wallet(2), wallet(1)),
+        );
+        assert_eq!(read_claims(&path).unwrap(), vec![(wallet(2), 10), (wallet(1), 20)]);
+    }
+
+    #[test]
+    fn rejects_bad_rows() {
+        let zero = csv("zero", &format!("wallet,amount\n{},0\n", wallet(1)));
+        assert!(read_claims(&zero).unwrap_err().to_string().contains("line 2: zero amount"));
+
+        let duplicate = csv(
+            "duplicate",
+            &format!("wallet,amount\n{},1\n{},2\n", wallet(1), wallet(1)),
+        );
+        let err = read_claims(&duplicate).unwrap_err().to_string();
+        assert!(err.contains("line 3: duplicate wallet"));
+
+        let empty = csv("empty", "wallet,amount\n");
+        assert!(read_claims(&empty).is_err());
+    }
+}